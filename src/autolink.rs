@@ -0,0 +1,305 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Linking of issue references and mentions.
+
+use alloc::{borrow::Cow, string::String};
+
+use crate::{handle_code_block, scan_comments, split_link, trim_start};
+
+/// Turns issue references and mentions in markdown into links.
+///
+/// The following references are recognized:
+///
+/// - `#N` and `GH-N`: an issue or pull request in the repository
+/// - `owner/repo#N`: an issue or pull request in another repository on the
+///   same host
+/// - `@user`: a user on the same host
+///
+/// References in code spans, code blocks, HTML comments, HTML tags, URLs, and
+/// existing links are left as is.
+///
+/// # Examples
+///
+/// ```
+/// use parse_changelog::AutoLinker;
+///
+/// let linker = AutoLinker::new("https://github.com/owner/repo");
+/// assert_eq!(
+///     linker.link("- Fix crash (#12, thanks @user)"),
+///     "- Fix crash ([#12](https://github.com/owner/repo/issues/12), \
+///      thanks [@user](https://github.com/user))",
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct AutoLinker {
+    repository: String,
+    issue_url: Option<String>,
+}
+
+impl AutoLinker {
+    /// Creates a new `AutoLinker` that links references to the repository at
+    /// the given URL (e.g., `https://github.com/owner/repo`).
+    #[must_use]
+    pub fn new(repository: &str) -> Self {
+        Self { repository: repository.trim_end_matches('/').into(), issue_url: None }
+    }
+
+    /// Sets the template of URLs of issues and pull requests.
+    ///
+    /// `{repository}` in the template is replaced with the repository URL,
+    /// and `{number}` is replaced with the issue number. The default is
+    /// `{repository}/issues/{number}`, which works for GitHub (GitHub
+    /// redirects it to the pull request if the number is a pull request).
+    /// For GitLab, use `{repository}/-/issues/{number}`.
+    pub fn issue_url(&mut self, template: &str) -> &mut Self {
+        self.issue_url = Some(template.into());
+        self
+    }
+
+    /// Returns the given text with references turned into links.
+    #[must_use]
+    pub fn link<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut out = String::with_capacity(text.len() + text.len() / 4);
+        walk(text, |part, s| match part {
+            Part::Text => self.link_text(s, &mut out),
+            Part::Code | Part::Link | Part::Verbatim => out.push_str(s),
+        });
+        // Linking always makes the text longer.
+        if out.len() == text.len() { Cow::Borrowed(text) } else { Cow::Owned(out) }
+    }
+
+    fn link_text(&self, s: &str, out: &mut String) {
+        let mut i = 0;
+        while i < s.len() {
+            let rest = &s[i..];
+            if is_boundary(s[..i].chars().next_back()) {
+                if let Some((reference, len)) = reference(rest) {
+                    match reference {
+                        Reference::Issue { repository: None, number } => {
+                            self.push_issue_link(&rest[..len], &self.repository, number, out);
+                        }
+                        Reference::Issue { repository: Some(repository), number } => {
+                            let mut url = String::from(self.host());
+                            url.push('/');
+                            url.push_str(repository);
+                            self.push_issue_link(&rest[..len], &url, number, out);
+                        }
+                        Reference::User(user) => {
+                            push_link(&rest[..len], &[self.host(), "/", user], out);
+                        }
+                    }
+                    i += len;
+                    continue;
+                }
+            }
+            let c = rest.chars().next().unwrap();
+            out.push(c);
+            i += c.len_utf8();
+        }
+    }
+
+    fn push_issue_link(&self, text: &str, repository: &str, number: &str, out: &mut String) {
+        match &self.issue_url {
+            Some(template) => {
+                let url = template.replace("{repository}", repository).replace("{number}", number);
+                push_link(text, &[&url], out);
+            }
+            None => push_link(text, &[repository, "/issues/", number], out),
+        }
+    }
+
+    /// Returns the URL of the host of the repository. (e.g.,
+    /// `https://gitlab.com` for `https://gitlab.com/group/subgroup/repo`)
+    fn host(&self) -> &str {
+        let host_start = self.repository.find("://").map_or(0, |pos| pos + 3);
+        match self.repository[host_start..].find('/') {
+            Some(pos) => &self.repository[..host_start + pos],
+            None => &self.repository,
+        }
+    }
+}
+
+fn push_link(text: &str, url: &[&str], out: &mut String) {
+    out.push('[');
+    out.push_str(text);
+    out.push_str("](");
+    for s in url {
+        out.push_str(s);
+    }
+    out.push(')');
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Part {
+    /// Text that may contain references.
+    Text,
+    /// A line in code blocks, or a code span.
+    Code,
+    /// A link, or a line of link reference definition.
+    Link,
+    /// HTML comments, HTML tags, URLs, and escaped characters.
+    Verbatim,
+}
+
+/// Splits markdown text into parts, and calls `f` with each part in order.
+pub(crate) fn walk<'a>(text: &'a str, mut f: impl FnMut(Part, &'a str)) {
+    // If `Some`, we are in a code block (``` or ~~~).
+    let mut on_code_block: Option<&[u8]> = None;
+    // If `true`, we are in a comment (`<!--` and `-->`).
+    let mut on_comment = false;
+    for line in text.split_inclusive('\n') {
+        let trimmed = trim_start(line.trim_end_matches('\n'));
+        if handle_code_block(&mut on_code_block, on_comment, trimmed.as_bytes()) {
+            f(Part::Code, line);
+            continue;
+        }
+        if !on_comment {
+            // [label]: destination
+            if trimmed.starts_with('[') {
+                if let Some((_, "", remaining)) = split_link(trimmed) {
+                    if remaining.starts_with(':') {
+                        f(Part::Link, line);
+                        continue;
+                    }
+                }
+            }
+        }
+        // Comments are found in the same way as the parser, and the text
+        // outside of them is split into parts.
+        let mut start = 0;
+        scan_comments(&mut on_comment, line.as_bytes(), true, |delim, open| {
+            if open {
+                walk_line(&line[start..delim.start], &mut f);
+                start = delim.start;
+            } else {
+                f(Part::Verbatim, &line[start..delim.end]);
+                start = delim.end;
+            }
+        });
+        if on_comment {
+            f(Part::Verbatim, &line[start..]);
+        } else {
+            walk_line(&line[start..], &mut f);
+        }
+    }
+}
+
+/// Splits a part of a line outside of comments into parts.
+fn walk_line<'a>(line: &'a str, f: &mut impl FnMut(Part, &'a str)) {
+    let mut text_start = 0;
+    let mut i = 0;
+    while i < line.len() {
+        let rest = &line[i..];
+        let (part, len) = match rest.as_bytes()[0] {
+            b'\\' => (Part::Verbatim, 1 + rest[1..].chars().next().map_or(0, char::len_utf8)),
+            // <https://example.com/#1>, <a href="#1">
+            b'<' => (Part::Verbatim, rest.find('>').map_or(0, |pos| pos + 1)),
+            // `#1`
+            b'`' => {
+                let n = rest.bytes().take_while(|&b| b == b'`').count();
+                match rest[n..].find(&rest[..n]) {
+                    Some(pos) => (Part::Code, n + pos + n),
+                    None => (Part::Verbatim, n),
+                }
+            }
+            // [#1](https://example.com)
+            b'[' => (
+                Part::Link,
+                split_link(rest).map_or(0, |(_, _, remaining)| rest.len() - remaining.len()),
+            ),
+            // https://example.com/#1
+            b'h' if is_boundary(line[..i].chars().next_back())
+                && (rest.starts_with("https://") || rest.starts_with("http://")) =>
+            {
+                (Part::Verbatim, rest.find(char::is_whitespace).unwrap_or(rest.len()))
+            }
+            _ => (Part::Text, 0),
+        };
+        if len == 0 {
+            i += rest.chars().next().unwrap().len_utf8();
+            continue;
+        }
+        if text_start != i {
+            f(Part::Text, &line[text_start..i]);
+        }
+        f(part, &rest[..len]);
+        i += len;
+        text_start = i;
+    }
+    if text_start != line.len() {
+        f(Part::Text, &line[text_start..]);
+    }
+}
+
+pub(crate) enum Reference<'a> {
+    /// `#N` or `GH-N` (`repository` is `None`), or `owner/repo#N`.
+    Issue { repository: Option<&'a str>, number: &'a str },
+    /// `@user`
+    User(&'a str),
+}
+
+/// Parses a reference at the start of `s`, and returns it and its length.
+/// Returns `None` if `s` doesn't start with a reference.
+///
+/// The caller must check that the previous character is a word boundary (see
+/// [`is_boundary`]).
+pub(crate) fn reference(s: &str) -> Option<(Reference<'_>, usize)> {
+    // #N
+    if let Some(rest) = s.strip_prefix('#') {
+        let len = 1 + number_len(rest)?;
+        return Some((Reference::Issue { repository: None, number: &s[1..len] }, len));
+    }
+    // GH-N
+    if let Some(rest) = s.strip_prefix("GH-") {
+        let len = 3 + number_len(rest)?;
+        return Some((Reference::Issue { repository: None, number: &s[3..len] }, len));
+    }
+    // @user
+    if let Some(rest) = s.strip_prefix('@') {
+        let user_len = rest.bytes().take_while(|&b| b.is_ascii_alphanumeric() || b == b'-').count();
+        // Ignore npm scoped packages (e.g., `@types/node`).
+        if user_len == 0
+            || user_len > 39
+            || rest.starts_with('-')
+            || !is_end(&rest[user_len..])
+            || rest[user_len..].starts_with('/')
+        {
+            return None;
+        }
+        return Some((Reference::User(&rest[..user_len]), 1 + user_len));
+    }
+    // owner/repo#N
+    let owner_len = s.bytes().take_while(|&b| b.is_ascii_alphanumeric() || b == b'-').count();
+    let rest = s[owner_len..].strip_prefix('/').filter(|_| owner_len != 0)?;
+    let repo_len = rest
+        .bytes()
+        .take_while(|&b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+        .count();
+    let rest = rest[repo_len..].strip_prefix('#').filter(|_| repo_len != 0)?;
+    let number_start = owner_len + 1 + repo_len + 1;
+    let len = number_start + number_len(rest)?;
+    let reference = Reference::Issue {
+        repository: Some(&s[..number_start - 1]),
+        number: &s[number_start..len],
+    };
+    Some((reference, len))
+}
+
+/// Returns `true` if a reference can start after the given character.
+pub(crate) fn is_boundary(prev: Option<char>) -> bool {
+    prev.is_none_or(|c| {
+        !c.is_alphanumeric() && !matches!(c, '_' | '-' | '.' | '/' | '&' | '#' | '@')
+    })
+}
+
+/// Returns `true` if a reference can end before the given text.
+fn is_end(s: &str) -> bool {
+    s.chars().next().is_none_or(|c| !c.is_alphanumeric() && c != '_')
+}
+
+/// Returns the length of the number at the start of `s` if `s` starts with a
+/// number that ends at a word boundary.
+fn number_len(s: &str) -> Option<usize> {
+    let len = s.bytes().take_while(u8::is_ascii_digit).count();
+    (len != 0 && is_end(&s[len..])).then_some(len)
+}
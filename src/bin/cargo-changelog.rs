@@ -0,0 +1,10 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// `cargo changelog`
+//
+// This is the same program as `parse-changelog`, but always runs its
+// `changelog` subcommand. See `try_main` in src/cli.rs.
+
+#![forbid(unsafe_code)]
+
+include!("../cli.rs");
@@ -0,0 +1,967 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// The command-line interface shared by `parse-changelog` and `cargo-changelog`
+// binaries. This file is included by src/main.rs and src/bin/cargo-changelog.rs.
+
+use std::{
+    borrow::Cow,
+    fs,
+    io::{self, BufWriter, Read as _, Write as _},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use indexmap::IndexMap;
+use lexopt::{
+    Arg::{Long, Short, Value},
+    ValueExt as _,
+};
+use parse_changelog::{Changelog, Parser, Release};
+
+type Result<T, E = Box<dyn std::error::Error + Send + Sync>> = std::result::Result<T, E>;
+
+macro_rules! bail {
+    ($($tt:tt)*) => {
+        return Err(format!($($tt)*).into())
+    };
+}
+
+macro_rules! parse_flag {
+    ($arg:ident, $flag:ident $(,)?) => {{
+        if std::mem::replace(&mut $flag, true) {
+            crate::multi_arg(&$arg)?;
+        }
+    }};
+}
+
+macro_rules! parse_opt {
+    ($parser:ident, $arg:ident, $opt:ident $(,)?) => {{
+        if $opt.is_some() {
+            crate::multi_arg(&$arg)?;
+        }
+        $opt = Some(lexopt::ValueExt::parse(&$parser.value()?)?);
+    }};
+}
+
+#[path = "cli/assemble.rs"]
+mod assemble;
+#[path = "cli/cargo.rs"]
+mod cargo;
+#[path = "cli/changeset.rs"]
+mod changeset;
+#[path = "cli/check_version.rs"]
+mod check_version;
+#[path = "cli/compare_links.rs"]
+mod compare_links;
+#[path = "cli/deps.rs"]
+mod deps;
+#[path = "cli/diff.rs"]
+mod diff;
+#[path = "cli/entries.rs"]
+mod entries;
+#[path = "cli/fmt.rs"]
+mod fmt;
+#[path = "cli/generate.rs"]
+mod generate;
+#[path = "cli/git.rs"]
+mod git;
+#[path = "cli/tags.rs"]
+mod tags;
+#[path = "cli/version.rs"]
+mod version;
+#[path = "cli/workspace.rs"]
+mod workspace;
+
+static USAGE: &str = "parse-changelog
+
+Parse a changelog and output a release note for the specified version.
+
+USAGE:
+    parse-changelog [OPTIONS] <PATH> [VERSION]
+    parse-changelog --workspace [OPTIONS]
+    parse-changelog <SUBCOMMAND> [OPTIONS]
+
+SUBCOMMANDS:
+    assemble         Assemble news fragments into a new release section
+    changeset        Write release sections from Changesets
+    check-version    Check that the changelog has a release note for the manifest version
+    compare-links    Maintain link reference definitions of releases (compare links)
+    deps             Output changelogs of dependencies updated between two Cargo.lock files
+    diff             Compare releases in two changelogs
+    fmt              Normalize the formatting of the changelog
+    generate         Generate changelog entries from Conventional Commits
+    tags             Compare versions in changelog with git tags
+
+ARGS:
+    <PATH>       Path to the changelog file (use '-' for standard input, or
+                 'git:<REV>:<PATH>' to read the file at the specified git revision)
+    [VERSION]    Specify version (by default, select the latest release)
+
+OPTIONS:
+    -t, --title                       Output title instead of a note
+        --title-no-link               Similar to --title, but remove links from title
+        --json                        Output JSON representation of all releases in changelog
+        --format <FORMAT>             Output format of a note [possible values: markdown, html,
+                                      text] [default: markdown]
+        --heading-offset <N>          Shift levels of all headings in the note by N (negative N
+                                      promotes headings; with --format html, the heading of the
+                                      release is also demoted)
+        --top-heading-level <N>       Shift levels of all headings in the note so that the top-level
+                                      heading has level N
+        --anchors                     Add an anchor to the heading of the release (requires
+                                      --format html)
+        --standalone                  Output a complete HTML document instead of an HTML fragment
+                                      (requires --format html)
+        --width <N>                   Reflow paragraphs and list items to N columns (requires
+                                      --format text)
+        --no-link-urls                Remove URLs of links instead of writing them in parentheses
+                                      after the link text (requires --format text)
+        --strip-comments              Remove HTML comments from the output
+        --base-url <URL>              Resolve relative links in the output against URL ('{rev}' in
+                                      URL is replaced with the revision specified by --rev or
+                                      'git:<REV>:<PATH>', or HEAD)
+        --auto-link <URL>             Turn issue references (#N, GH-N, owner/repo#N) and @mentions
+                                      in the output into links to the repository at URL (e.g.,
+                                      https://github.com/owner/repo)
+        --issue-url <TEMPLATE>        URL of issues linked by --auto-link ('{repository}' and
+                                      '{number}' in TEMPLATE are replaced with the repository URL
+                                      and the issue number) [default:
+                                      {repository}/issues/{number}]
+        --max-bytes <N>               Truncate the note to at most N bytes at a paragraph or list
+                                      item boundary
+        --max-lines <N>               Truncate the note to at most N lines at a paragraph or list
+                                      item boundary
+        --full-changelog-url <URL>    Append a link to the section of the release in the changelog
+                                      at URL to the truncated note (requires --max-bytes or
+                                      --max-lines)
+        --full-changelog-text <TEXT>  Text of the link added by --full-changelog-url [default: See
+                                      the full changelog]
+        --list                        List versions in changelog, one per line
+        --columns <COLUMNS>           Comma-separated list of columns to output with --list
+                                      [possible values: version, title, date, line] [default: version]
+        --reverse                     Reverse the order of --list output
+        --sort <ORDER>                Sort order of --list output [possible values: document, semver]
+                                      [default: document]
+        --version-format <PATTERN>    Specify version format
+        --prefix-format <PATTERN>     Specify prefix format [aliases: prefix]
+        --package <NAME>              Only use releases of the specified package (requires
+                                      --prefix-format with a capture group named 'package')
+        --rev <REV>                   Read the changelog at the specified git revision instead of
+                                      the working tree
+        --workspace                   Output release notes for the current version of each package
+                                      in the Cargo workspace (read from CHANGELOG.md in the
+                                      directory of each package)
+        --manifest-path <PATH>        Path to Cargo.toml of the workspace (requires --workspace)
+                                      [default: Cargo.toml]
+    -h, --help                        Print help information
+    -V, --version                     Print version information
+";
+
+#[derive(Default)]
+struct Args {
+    path: PathBuf,
+    release: Option<String>,
+    title: bool,
+    title_no_link: bool,
+    json: bool,
+    list: bool,
+    columns: Option<Columns>,
+    reverse: bool,
+    sort: Option<Sort>,
+    format: Option<Format>,
+    heading_offset: Option<i8>,
+    top_heading_level: Option<u8>,
+    anchors: bool,
+    standalone: bool,
+    width: Option<usize>,
+    no_link_urls: bool,
+    strip_comments: bool,
+    base_url: Option<String>,
+    auto_link: Option<String>,
+    issue_url: Option<String>,
+    max_bytes: Option<usize>,
+    max_lines: Option<usize>,
+    full_changelog_url: Option<String>,
+    full_changelog_text: Option<String>,
+    version_format: Option<String>,
+    prefix_format: Option<String>,
+    package: Option<String>,
+    workspace: bool,
+    manifest_path: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Column {
+    Version,
+    Title,
+    Date,
+    Line,
+}
+
+struct Columns(Vec<Column>);
+
+impl std::str::FromStr for Columns {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut columns = Vec::with_capacity(4);
+        for column in s.split(',') {
+            columns.push(match column.trim() {
+                "version" => Column::Version,
+                "title" => Column::Title,
+                "date" => Column::Date,
+                "line" => Column::Line,
+                _ => {
+                    return Err(format!(
+                        "unknown column '{column}' (expected version, title, date, or line)"
+                    ));
+                }
+            });
+        }
+        Ok(Self(columns))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Sort {
+    Document,
+    Semver,
+}
+
+impl std::str::FromStr for Sort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "document" => Ok(Self::Document),
+            "semver" => Ok(Self::Semver),
+            _ => Err(format!("unknown sort order '{s}' (expected document or semver)")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Markdown,
+    Html,
+    Text,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            "text" => Ok(Self::Text),
+            _ => Err(format!("unknown output format '{s}' (expected markdown, html, or text)")),
+        }
+    }
+}
+
+impl Args {
+    fn parse() -> Result<Option<Self>> {
+        let mut path: Option<PathBuf> = None;
+        let mut release = None;
+        let mut title = false;
+        let mut title_no_link = false;
+        let mut json = false;
+        let mut list = false;
+        let mut columns = None;
+        let mut reverse = false;
+        let mut sort = None;
+        let mut format = None;
+        let mut heading_offset = None;
+        let mut top_heading_level = None;
+        let mut anchors = false;
+        let mut standalone = false;
+        let mut width = None;
+        let mut no_link_urls = false;
+        let mut strip_comments = false;
+        let mut base_url = None;
+        let mut auto_link = None;
+        let mut issue_url = None;
+        let mut max_bytes = None;
+        let mut max_lines = None;
+        let mut full_changelog_url = None;
+        let mut full_changelog_text = None;
+        let mut version_format = None;
+        let mut prefix_format = None;
+        let mut package = None;
+        let mut rev: Option<String> = None;
+        let mut workspace = false;
+        let mut manifest_path = None;
+
+        let mut parser = lexopt::Parser::from_env();
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Short('t') | Long("title") => parse_flag!(arg, title),
+                Long("title-no-link") => parse_flag!(arg, title_no_link),
+                Long("json") => parse_flag!(arg, json),
+                Long("list") => parse_flag!(arg, list),
+                Long("columns") => parse_opt!(parser, arg, columns),
+                Long("reverse") => parse_flag!(arg, reverse),
+                Long("sort") => parse_opt!(parser, arg, sort),
+                Long("format") => parse_opt!(parser, arg, format),
+                Long("heading-offset") => parse_opt!(parser, arg, heading_offset),
+                Long("top-heading-level") => parse_opt!(parser, arg, top_heading_level),
+                Long("anchors") => parse_flag!(arg, anchors),
+                Long("standalone") => parse_flag!(arg, standalone),
+                Long("width") => parse_opt!(parser, arg, width),
+                Long("no-link-urls") => parse_flag!(arg, no_link_urls),
+                Long("strip-comments") => parse_flag!(arg, strip_comments),
+                Long("base-url") => parse_opt!(parser, arg, base_url),
+                Long("auto-link") => parse_opt!(parser, arg, auto_link),
+                Long("issue-url") => parse_opt!(parser, arg, issue_url),
+                Long("max-bytes") => parse_opt!(parser, arg, max_bytes),
+                Long("max-lines") => parse_opt!(parser, arg, max_lines),
+                Long("full-changelog-url") => parse_opt!(parser, arg, full_changelog_url),
+                Long("full-changelog-text") => parse_opt!(parser, arg, full_changelog_text),
+                Long("version-format") => parse_opt!(parser, arg, version_format),
+                Long("prefix-format" | "prefix") => parse_opt!(parser, arg, prefix_format),
+                Long("package") => parse_opt!(parser, arg, package),
+                Long("rev") => parse_opt!(parser, arg, rev),
+                Long("workspace") => parse_flag!(arg, workspace),
+                Long("manifest-path") => parse_opt!(parser, arg, manifest_path),
+                Short('h') | Long("help") => {
+                    print!("{USAGE}");
+                    return Ok(None);
+                }
+                Short('V') | Long("version") => {
+                    println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+                    return Ok(None);
+                }
+                Value(val) if path.is_none() => path = Some(val.into()),
+                Value(val) if release.is_none() => release = Some(val.parse()?),
+                _ => return Err(arg.unexpected().into()),
+            }
+        }
+
+        if workspace {
+            if path.is_some() {
+                bail!("--workspace may not be used together with <PATH>");
+            }
+            if title {
+                conflicts("--workspace", "--title")?;
+            }
+            if title_no_link {
+                conflicts("--workspace", "--title-no-link")?;
+            }
+            if list {
+                conflicts("--workspace", "--list")?;
+            }
+            if package.is_some() {
+                conflicts("--workspace", "--package")?;
+            }
+            if rev.is_some() {
+                conflicts("--workspace", "--rev")?;
+            }
+        } else if manifest_path.is_some() {
+            requires("--manifest-path", "--workspace")?;
+        }
+        let Some(mut path) = path.or_else(|| workspace.then(PathBuf::new)) else {
+            bail!("no changelog path specified")
+        };
+        if let Some(rev) = rev {
+            if path.as_os_str() == "-" {
+                bail!("--rev may not be used together with standard input");
+            }
+            if git_path(&path).is_some() {
+                bail!("--rev may not be used together with 'git:<REV>:<PATH>' path");
+            }
+            let mut git_path = std::ffi::OsString::from(format!("git:{rev}:"));
+            git_path.push(path);
+            path = git_path.into();
+        }
+        if title && title_no_link {
+            conflicts("--title", "--title-no-link")?;
+        }
+        let format_flag = match format {
+            Some(Format::Markdown) | None => None,
+            Some(Format::Html) => Some("--format html"),
+            Some(Format::Text) => Some("--format text"),
+        };
+        if let Some(format_flag) = format_flag {
+            if title {
+                conflicts(format_flag, "--title")?;
+            }
+            if title_no_link {
+                conflicts(format_flag, "--title-no-link")?;
+            }
+            if json {
+                conflicts(format_flag, "--json")?;
+            }
+            if list {
+                conflicts(format_flag, "--list")?;
+            }
+            if workspace {
+                conflicts(format_flag, "--workspace")?;
+            }
+        }
+        if heading_offset.is_some() && top_heading_level.is_some() {
+            conflicts("--heading-offset", "--top-heading-level")?;
+        }
+        for (flag, set) in [
+            ("--heading-offset", heading_offset.is_some()),
+            ("--top-heading-level", top_heading_level.is_some()),
+        ] {
+            if !set {
+                continue;
+            }
+            if title {
+                conflicts(flag, "--title")?;
+            }
+            if title_no_link {
+                conflicts(flag, "--title-no-link")?;
+            }
+            if json {
+                conflicts(flag, "--json")?;
+            }
+            if list {
+                conflicts(flag, "--list")?;
+            }
+            if workspace {
+                conflicts(flag, "--workspace")?;
+            }
+            if format == Some(Format::Text) {
+                conflicts("--format text", flag)?;
+            }
+        }
+        if top_heading_level.is_some_and(|level| !(1..=6).contains(&level)) {
+            bail!("--top-heading-level must be between 1 and 6");
+        }
+        if format == Some(Format::Html) {
+            if heading_offset.is_some_and(|offset| offset < 0) {
+                bail!("negative --heading-offset may not be used together with --format html");
+            }
+            if top_heading_level.is_some() {
+                conflicts("--format html", "--top-heading-level")?;
+            }
+        } else {
+            if anchors {
+                requires("--anchors", "--format html")?;
+            }
+            if standalone {
+                requires("--standalone", "--format html")?;
+            }
+        }
+        if format != Some(Format::Text) {
+            if width.is_some() {
+                requires("--width", "--format text")?;
+            }
+            if no_link_urls {
+                requires("--no-link-urls", "--format text")?;
+            }
+        }
+        if strip_comments {
+            if list {
+                conflicts("--strip-comments", "--list")?;
+            }
+            if workspace {
+                conflicts("--strip-comments", "--workspace")?;
+            }
+        }
+        if base_url.is_some() {
+            if json {
+                conflicts("--base-url", "--json")?;
+            }
+            if list {
+                conflicts("--base-url", "--list")?;
+            }
+            if workspace {
+                conflicts("--base-url", "--workspace")?;
+            }
+        }
+        if auto_link.is_some() {
+            if json {
+                conflicts("--auto-link", "--json")?;
+            }
+            if list {
+                conflicts("--auto-link", "--list")?;
+            }
+            if workspace {
+                conflicts("--auto-link", "--workspace")?;
+            }
+        }
+        for (flag, set) in
+            [("--max-bytes", max_bytes.is_some()), ("--max-lines", max_lines.is_some())]
+        {
+            if !set {
+                continue;
+            }
+            if title {
+                conflicts(flag, "--title")?;
+            }
+            if title_no_link {
+                conflicts(flag, "--title-no-link")?;
+            }
+            if json {
+                conflicts(flag, "--json")?;
+            }
+            if list {
+                conflicts(flag, "--list")?;
+            }
+            if workspace {
+                conflicts(flag, "--workspace")?;
+            }
+            match format {
+                Some(Format::Html) => conflicts("--format html", flag)?,
+                Some(Format::Text) => conflicts("--format text", flag)?,
+                _ => {}
+            }
+        }
+        if max_bytes.is_none() && max_lines.is_none() && full_changelog_url.is_some() {
+            requires("--full-changelog-url", "--max-bytes or --max-lines")?;
+        }
+        if issue_url.is_some() && auto_link.is_none() {
+            requires("--issue-url", "--auto-link")?;
+        }
+        if full_changelog_text.is_some() && full_changelog_url.is_none() {
+            requires("--full-changelog-text", "--full-changelog-url")?;
+        }
+        if package.is_some() && prefix_format.is_none() {
+            requires("--package", "--prefix-format")?;
+        }
+        if list {
+            if title {
+                conflicts("--list", "--title")?;
+            }
+            if title_no_link {
+                conflicts("--list", "--title-no-link")?;
+            }
+            if json {
+                conflicts("--list", "--json")?;
+            }
+            if release.is_some() {
+                bail!("--list may not be used together with [VERSION]");
+            }
+        } else {
+            if columns.is_some() {
+                requires("--columns", "--list")?;
+            }
+            if reverse {
+                requires("--reverse", "--list")?;
+            }
+            if sort.is_some() {
+                requires("--sort", "--list")?;
+            }
+        }
+
+        Ok(Some(Self {
+            path,
+            release,
+            title,
+            title_no_link,
+            json,
+            list,
+            columns,
+            reverse,
+            sort,
+            format,
+            heading_offset,
+            top_heading_level,
+            anchors,
+            standalone,
+            width,
+            no_link_urls,
+            strip_comments,
+            base_url,
+            auto_link,
+            issue_url,
+            max_bytes,
+            max_lines,
+            full_changelog_url,
+            full_changelog_text,
+            version_format,
+            prefix_format,
+            package,
+            workspace,
+            manifest_path,
+        }))
+    }
+
+    fn path_for_msg(&self) -> &Path {
+        path_for_msg(&self.path)
+    }
+}
+
+fn format_arg(arg: &lexopt::Arg<'_>) -> String {
+    match arg {
+        Long(flag) => format!("--{flag}"),
+        Short(flag) => format!("-{flag}"),
+        Value(val) => val.parse().unwrap(),
+    }
+}
+#[cold]
+#[inline(never)]
+fn multi_arg(flag: &lexopt::Arg<'_>) -> Result<()> {
+    let flag = &format_arg(flag);
+    bail!("the argument '{flag}' was provided more than once, but cannot be used multiple times");
+}
+#[cold]
+#[inline(never)]
+fn conflicts(a: &str, b: &str) -> Result<()> {
+    bail!("{a} may not be used together with {b}");
+}
+#[cold]
+#[inline(never)]
+fn requires(a: &str, b: &str) -> Result<()> {
+    bail!("{a} can only be used together with {b}");
+}
+
+fn main() -> ExitCode {
+    if let Err(e) = try_main() {
+        eprintln!("error: {e}");
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn try_main() -> Result<()> {
+    if env!("CARGO_BIN_NAME") == "cargo-changelog" {
+        // Cargo runs `cargo-changelog changelog [ARGS]...` for `cargo changelog [ARGS]...`.
+        // Also allow running this directly as `cargo-changelog [ARGS]...`.
+        let mut args = std::env::args_os().skip(1).peekable();
+        if args.peek().is_some_and(|arg| arg == "changelog") {
+            args.next();
+        }
+        return cargo::run(lexopt::Parser::from_args(args));
+    }
+    // A path to an existing file takes precedence over a subcommand of the same
+    // name, for compatibility with `parse-changelog <PATH>`.
+    if let Some(subcommand) = std::env::args_os().nth(1).filter(|arg| !Path::new(arg).exists()) {
+        let args = || std::env::args_os().skip(2);
+        match subcommand.to_str() {
+            Some("assemble") => return assemble::run(lexopt::Parser::from_args(args())),
+            Some("changeset") => return changeset::run(lexopt::Parser::from_args(args())),
+            Some("check-version") => {
+                return check_version::run(lexopt::Parser::from_args(args()));
+            }
+            Some("compare-links") => {
+                return compare_links::run(lexopt::Parser::from_args(args()));
+            }
+            Some("deps") => return deps::run(lexopt::Parser::from_args(args())),
+            Some("diff") => return diff::run(lexopt::Parser::from_args(args())),
+            Some("fmt") => return fmt::run(lexopt::Parser::from_args(args())),
+            Some("generate") => return generate::run(lexopt::Parser::from_args(args())),
+            Some("tags") => return tags::run(lexopt::Parser::from_args(args())),
+            _ => {}
+        }
+    }
+
+    let Some(args) = Args::parse()? else { return Ok(()) };
+    run(&args)
+}
+
+fn run(args: &Args) -> Result<()> {
+    let parser = new_parser(args.version_format.as_deref(), args.prefix_format.as_deref())?;
+    if args.workspace {
+        let manifest_path = args.manifest_path.as_deref().unwrap_or(Path::new("Cargo.toml"));
+        return workspace::run(&parser, manifest_path, args.json);
+    }
+    let text = read_changelog(&args.path)?;
+    let changelog = match &args.package {
+        Some(package) => parse_package(&parser, &text, &args.path, package)?,
+        None => parse(&parser, &text, &args.path)?,
+    };
+
+    if args.json {
+        let mut stdout = BufWriter::new(io::stdout().lock()); // Buffered because it is written many times.
+        let notes: Vec<_> = changelog
+            .values()
+            .map(|release| {
+                if args.strip_comments {
+                    release.notes_without_comments()
+                } else {
+                    release.notes.into()
+                }
+            })
+            .collect();
+        let releases: Vec<_> = changelog
+            .values()
+            .zip(&notes)
+            .map(|(release, notes)| {
+                let mut release = release.clone();
+                release.notes = notes;
+                release
+            })
+            .collect();
+        let changelog: IndexMap<_, _> = changelog
+            .keys()
+            .zip(&releases)
+            .map(|(version, release)| (*version, JsonRelease(release)))
+            .collect();
+        serde_json::to_writer(&mut stdout, &changelog)?;
+        stdout.flush()?;
+        return Ok(());
+    }
+
+    if args.list {
+        return print_list(args, &text, &changelog);
+    }
+
+    let release = if let Some(version) = args.release.as_deref() {
+        if let Some(release) = changelog.get(version) {
+            release
+        } else {
+            bail!("not found release note for '{version}' in {}", args.path_for_msg().display());
+        }
+    } else {
+        let (entry_key, entry_value) = changelog.first().unwrap(); // unwrap is okay as Parser::parse returns an error if changelog is empty.
+        if entry_key == &"Unreleased" {
+            changelog
+                .get_index(1)
+                .ok_or_else(|| {
+                    format!(
+                        "not found release; to get 'Unreleased' section specify release \
+                         explicitly: `parse-changelog {} Unreleased`",
+                        args.path.display()
+                    )
+                })?
+                .1
+        } else {
+            entry_value
+        }
+    };
+    let mut title = Cow::Borrowed(release.title);
+    let mut notes = Cow::Borrowed(release.notes);
+    if args.strip_comments {
+        notes = release.notes_without_comments();
+    }
+    if let Some(base_url) = &args.base_url {
+        let mut resolver = parse_changelog::LinkResolver::new(base_url);
+        if let Some((rev, _)) = git_path(&args.path) {
+            resolver.revision(rev);
+        }
+        title = resolver.resolve(release.title);
+        notes = resolver.resolve(&notes).into_owned().into();
+    }
+    if let Some(repository) = &args.auto_link {
+        let mut linker = parse_changelog::AutoLinker::new(repository);
+        if let Some(template) = &args.issue_url {
+            linker.issue_url(template);
+        }
+        title = linker.link(&title).into_owned().into();
+        notes = linker.link(&notes).into_owned().into();
+    }
+    let mut release = release.clone();
+    release.title = &title;
+    release.notes = &notes;
+    let text = if args.title {
+        release.title.into()
+    } else if args.title_no_link {
+        release.title_no_link()
+    } else if args.format == Some(Format::Html) {
+        let mut options = parse_changelog::HtmlOptions::new();
+        options
+            .heading_offset(args.heading_offset.map_or(0, i8::unsigned_abs))
+            .anchors(args.anchors)
+            .standalone(args.standalone);
+        let mut html = parse_changelog::render_html([&release], &options);
+        html.truncate(html.trim_end().len());
+        html.into()
+    } else if args.format == Some(Format::Text) {
+        let mut options = parse_changelog::PlainTextOptions::new();
+        options.link_urls(!args.no_link_urls);
+        if let Some(width) = args.width {
+            options.width(width);
+        }
+        release.notes_plain_with(&options).into()
+    } else {
+        let notes = if let Some(offset) = args.heading_offset {
+            release.notes_with_heading_offset(offset)
+        } else if let Some(level) = args.top_heading_level {
+            release.notes_with_top_heading_level(level)
+        } else {
+            release.notes.into()
+        };
+        if args.max_bytes.is_some() || args.max_lines.is_some() {
+            let mut options = parse_changelog::TruncateOptions::new();
+            if let Some(max_bytes) = args.max_bytes {
+                options.max_bytes(max_bytes);
+            }
+            if let Some(max_lines) = args.max_lines {
+                options.max_lines(max_lines);
+            }
+            if let Some(url) = &args.full_changelog_url {
+                options.changelog_url(url);
+            }
+            if let Some(text) = &args.full_changelog_text {
+                options.link_text(text);
+            }
+            let mut release = release.clone();
+            release.notes = &notes;
+            release.notes_truncated(&options).into_owned().into()
+        } else {
+            notes
+        }
+    };
+    let mut stdout = io::stdout().lock(); // Not buffered because it is written only a few times.
+    stdout.write_all(text.as_bytes())?;
+    stdout.write_all(b"\n")?;
+    stdout.flush()?;
+
+    Ok(())
+}
+
+fn new_parser(version_format: Option<&str>, prefix_format: Option<&str>) -> Result<Parser> {
+    let mut parser = Parser::new();
+    if let Some(version_format) = version_format {
+        parser.version_format(version_format)?;
+    }
+    if let Some(prefix_format) = prefix_format {
+        parser.prefix_format(prefix_format)?;
+    }
+    Ok(parser)
+}
+
+/// Reads a changelog from the given path.
+///
+/// `-` means standard input and `git:<REV>:<PATH>` means the file at the
+/// specified revision in the local git repository.
+fn read_changelog(path: &Path) -> Result<String> {
+    if let Some((rev, path)) = git_path(path) {
+        git::show(rev, path)
+    } else if path.as_os_str() == "-" {
+        let mut buf = String::with_capacity(128);
+        io::stdin()
+            .read_to_string(&mut buf)
+            .map_err(|e| format!("failed to read from standard input: {e}"))?;
+        Ok(buf)
+    } else {
+        Ok(fs::read_to_string(path)
+            .map_err(|e| format!("failed to read from file `{}`: {e}", path.display()))?)
+    }
+}
+
+fn parse<'a>(parser: &Parser, text: &'a str, path: &Path) -> Result<Changelog<'a>> {
+    match parser.parse(text) {
+        Ok(changelog) => Ok(changelog),
+        Err(e) => bail!("{e} in {}", path_for_msg(path).display()),
+    }
+}
+
+/// Parses release notes of the specified package.
+fn parse_package<'a>(
+    parser: &Parser,
+    text: &'a str,
+    path: &Path,
+    package: &str,
+) -> Result<Changelog<'a>> {
+    let changelog = match parser.parse_packages(text) {
+        Ok(changelog) => changelog,
+        Err(e) => bail!("{e} in {}", path_for_msg(path).display()),
+    };
+    let changelog: Changelog<'_> = changelog
+        .into_iter()
+        .filter(|((p, _), _)| *p == Some(package))
+        .map(|((_, version), release)| (version, release))
+        .collect();
+    if changelog.is_empty() {
+        bail!(
+            "no release note was found for package '{package}' in {}",
+            path_for_msg(path).display()
+        );
+    }
+    Ok(changelog)
+}
+
+/// Splits `git:<REV>:<PATH>` into revision and path.
+fn git_path(path: &Path) -> Option<(&str, &str)> {
+    path.to_str()?.strip_prefix("git:")?.split_once(':')
+}
+
+fn path_for_msg(path: &Path) -> &Path {
+    if path.as_os_str() == "-" { Path::new("changelog (standard input)") } else { path }
+}
+
+/// A release with references in its notes, for `--json`.
+struct JsonRelease<'a, 'r>(&'r Release<'a>);
+
+impl serde_core::Serialize for JsonRelease<'_, '_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde_core::Serializer,
+    {
+        use serde_core::ser::SerializeStruct as _;
+        let release = self.0;
+        let mut state = serializer.serialize_struct("Release", 6)?;
+        state.serialize_field("version", &release.version)?;
+        state.serialize_field("title", &release.title)?;
+        state.serialize_field("notes", &release.notes)?;
+        state.serialize_field("package", &release.package)?;
+        state.serialize_field("anchor", &release.anchor())?;
+        state.serialize_field("references", &references_json(release))?;
+        state.end()
+    }
+}
+
+fn references_json(release: &Release<'_>) -> serde_json::Value {
+    let references = release.references();
+    serde_json::json!({
+        "issues": references.issues,
+        "commits": references.commits,
+        "mentions": references.mentions,
+    })
+}
+
+fn print_list(args: &Args, text: &str, changelog: &Changelog<'_>) -> Result<()> {
+    let mut releases: Vec<&Release<'_>> = changelog.values().collect();
+    if args.sort == Some(Sort::Semver) {
+        // Newest first, as in changelogs that follow Keep a Changelog.
+        releases.sort_by(|a, b| version::cmp(b.version, a.version));
+    }
+    if args.reverse {
+        releases.reverse();
+    }
+    let columns = args.columns.as_ref().map_or(&[Column::Version][..], |c| &c.0);
+    let mut stdout = BufWriter::new(io::stdout().lock()); // Buffered because it is written many times.
+    for release in releases {
+        for (i, &column) in columns.iter().enumerate() {
+            if i != 0 {
+                stdout.write_all(b"\t")?;
+            }
+            match column {
+                Column::Version => stdout.write_all(release.version.as_bytes())?,
+                Column::Title => stdout.write_all(release.title.as_bytes())?,
+                Column::Date => stdout.write_all(find_date(release.title).as_bytes())?,
+                Column::Line => write!(stdout, "{}", line_number(text, release))?,
+            }
+        }
+        stdout.write_all(b"\n")?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Returns the first date in YYYY-MM-DD format in the given title, or an empty
+/// string if not found.
+fn find_date(title: &str) -> &str {
+    let bytes = title.as_bytes();
+    for start in 0..bytes.len().saturating_sub(9) {
+        let date = &bytes[start..start + 10];
+        if date.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        }) {
+            return &title[start..start + 10];
+        }
+    }
+    ""
+}
+
+/// Returns the 1-based line number of the heading of the given release.
+fn line_number(text: &str, release: &Release<'_>) -> usize {
+    let offset = release.heading_range().start;
+    memchr::memchr_iter(b'\n', &text.as_bytes()[..offset]).count() + 1
+}
+
+/// Returns the byte offset of `s` in `text` if `s` is a subslice of `text`.
+///
+/// Release::notes is a subslice of the parsed text except when it is empty.
+fn subslice_offset(text: &str, s: &str) -> Option<usize> {
+    let offset = (s.as_ptr() as usize).checked_sub(text.as_ptr() as usize)?;
+    if s.is_empty() || offset + s.len() > text.len() { None } else { Some(offset) }
+}
@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{
+    fs,
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+};
+
+use lexopt::Arg::{Long, Short, Value};
+
+use crate::{
+    Result,
+    entries::{self, Category, Entries},
+};
+
+static USAGE: &str = "parse-changelog assemble
+
+Assemble news fragments into a new release section of the changelog.
+
+News fragments are files named `<NAME>.<TYPE>.md` (e.g., `123.fixed.md`) in the
+fragment directory, where <TYPE> is one of the Keep a Changelog categories
+(added, changed, deprecated, removed, fixed, or security). More than one
+fragment of the same type can be added with `<NAME>.<TYPE>.<N>.md` (e.g.,
+`123.fixed.1.md`). Files without <TYPE> (e.g., `README.md`), files other than
+markdown files, and hidden files are ignored. Files with unknown <TYPE> are
+ignored with a warning.
+
+By default, the new release section is printed to standard output. With
+--write, it is inserted above the latest release in the changelog.
+
+USAGE:
+    parse-changelog assemble [OPTIONS] <PATH> <VERSION>
+
+ARGS:
+    <PATH>       Path to the changelog file
+    <VERSION>    Version of the new release
+
+OPTIONS:
+        --dir <DIR>                   Path to the fragment directory [default: changelog.d in the
+                                      directory containing the changelog]
+        --date <DATE>                 Release date to be included in the title
+        --write                       Insert the new release section into the changelog
+        --delete                      Delete consumed fragments (requires --write)
+        --version-format <PATTERN>    Specify version format
+        --prefix-format <PATTERN>     Specify prefix format [aliases: prefix]
+    -h, --help                        Print help information
+";
+
+struct Args {
+    path: PathBuf,
+    version: String,
+    dir: Option<PathBuf>,
+    date: Option<String>,
+    write: bool,
+    delete: bool,
+    version_format: Option<String>,
+    prefix_format: Option<String>,
+}
+
+impl Args {
+    fn parse(mut parser: lexopt::Parser) -> Result<Option<Self>> {
+        let mut path: Option<PathBuf> = None;
+        let mut version: Option<String> = None;
+        let mut dir = None;
+        let mut date = None;
+        let mut write = false;
+        let mut delete = false;
+        let mut version_format = None;
+        let mut prefix_format = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("dir") => parse_opt!(parser, arg, dir),
+                Long("date") => parse_opt!(parser, arg, date),
+                Long("write") => parse_flag!(arg, write),
+                Long("delete") => parse_flag!(arg, delete),
+                Long("version-format") => parse_opt!(parser, arg, version_format),
+                Long("prefix-format" | "prefix") => parse_opt!(parser, arg, prefix_format),
+                Short('h') | Long("help") => {
+                    print!("{USAGE}");
+                    return Ok(None);
+                }
+                Value(val) if path.is_none() => path = Some(val.into()),
+                Value(val) if version.is_none() => {
+                    version = Some(lexopt::ValueExt::parse(&val)?);
+                }
+                _ => return Err(arg.unexpected().into()),
+            }
+        }
+
+        let Some(path) = path else { bail!("no changelog path specified") };
+        let Some(version) = version else { bail!("no version specified") };
+        if path.as_os_str() == "-" || crate::git_path(&path).is_some() {
+            bail!("assemble requires a path to a file in the working tree");
+        }
+        if delete && !write {
+            crate::requires("--delete", "--write")?;
+        }
+
+        Ok(Some(Self { path, version, dir, date, write, delete, version_format, prefix_format }))
+    }
+}
+
+/// Reads news fragments in the given directory, and returns entries and paths
+/// of the consumed fragments.
+fn read_fragments(dir: &Path) -> Result<(Entries, Vec<PathBuf>)> {
+    let read_dir = fs::read_dir(dir)
+        .map_err(|e| format!("failed to read directory `{}`: {e}", dir.display()))?;
+    let mut fragments = vec![];
+    for entry in read_dir {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else { continue };
+        if file_name.starts_with('.') {
+            continue;
+        }
+        let Some(stem) = file_name.strip_suffix(".md") else { continue };
+        // <NAME>.<TYPE>.<N>.md
+        let (stem, n) = match stem.rsplit_once('.') {
+            Some((rest, n)) if rest.contains('.') => match n.parse::<u64>() {
+                Ok(n) => (rest, n),
+                Err(_) => (stem, 0),
+            },
+            _ => (stem, 0),
+        };
+        let Some((name, kind)) = stem.rsplit_once('.') else { continue };
+        let Some(category) = Category::from_name(kind) else {
+            eprintln!("warning: ignoring `{}` with unknown fragment type '{kind}'", path.display());
+            continue;
+        };
+        fragments.push((category, name.to_owned(), n, path));
+    }
+    // Sort by name, numerically if the names are issue numbers.
+    fragments.sort_by(|(_, a, a_n, _), (_, b, b_n, _)| {
+        match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            _ => a.cmp(b),
+        }
+        .then(a_n.cmp(b_n))
+    });
+
+    let mut entries = Entries::new();
+    let mut paths = Vec::with_capacity(fragments.len());
+    for (category, .., path) in fragments {
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read from file `{}`: {e}", path.display()))?;
+        if !text.trim().is_empty() {
+            entries.entry(category).or_default().push(text);
+        }
+        paths.push(path);
+    }
+    Ok((entries, paths))
+}
+
+pub(crate) fn run(parser: lexopt::Parser) -> Result<()> {
+    let Some(args) = Args::parse(parser)? else { return Ok(()) };
+
+    let dir = match &args.dir {
+        Some(dir) => dir.clone(),
+        None => args.path.parent().unwrap_or_else(|| Path::new("")).join("changelog.d"),
+    };
+    let (entries, fragments) = read_fragments(&dir)?;
+    if entries.is_empty() {
+        bail!("no news fragments were found in `{}`", dir.display());
+    }
+    let title = match &args.date {
+        Some(date) => format!("{} - {date}", args.version),
+        None => args.version.clone(),
+    };
+
+    let parser = crate::new_parser(args.version_format.as_deref(), args.prefix_format.as_deref())?;
+    let text = crate::read_changelog(&args.path)?;
+    let (new, section) = entries::insert_release(&parser, &text, &args.version, &title, |level| {
+        entries::render(level, &entries)
+    })?;
+    if !args.write {
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "{}", new[section].trim_end())?;
+        stdout.flush()?;
+        return Ok(());
+    }
+    fs::write(&args.path, new)
+        .map_err(|e| format!("failed to write to file `{}`: {e}", args.path.display()))?;
+    if args.delete {
+        for path in fragments {
+            fs::remove_file(&path)
+                .map_err(|e| format!("failed to remove file `{}`: {e}", path.display()))?;
+        }
+    }
+    Ok(())
+}
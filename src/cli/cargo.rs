@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// `cargo changelog`
+
+use std::{env, path::PathBuf};
+
+use lexopt::{
+    Arg::{Long, Short, Value},
+    ValueExt as _,
+};
+
+use crate::{Result, workspace};
+
+static USAGE: &str = "cargo changelog
+
+Output a release note for the current version of the Cargo package.
+
+Reads `package.version` from Cargo.toml of the current package (the nearest
+Cargo.toml in the current directory or its parents) and the release note for that
+version from CHANGELOG.md in the same directory as Cargo.toml.
+
+USAGE:
+    cargo changelog [OPTIONS] [VERSION]
+
+ARGS:
+    [VERSION]    Specify version [default: package.version in Cargo.toml]
+
+OPTIONS:
+    -t, --title                       Output title instead of a note
+        --title-no-link               Similar to --title, but remove links from title
+        --json                        Output JSON representation of all releases in changelog
+        --all                         List all versions in changelog, one per line
+        --manifest-path <PATH>        Path to Cargo.toml
+        --version-format <PATTERN>    Specify version format
+        --prefix-format <PATTERN>     Specify prefix format [aliases: prefix]
+    -h, --help                        Print help information
+    -V, --version                     Print version information
+";
+
+pub(crate) fn run(mut parser: lexopt::Parser) -> Result<()> {
+    let mut release = None;
+    let mut title = false;
+    let mut title_no_link = false;
+    let mut json = false;
+    let mut all = false;
+    let mut manifest_path: Option<PathBuf> = None;
+    let mut version_format = None;
+    let mut prefix_format = None;
+
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Short('t') | Long("title") => parse_flag!(arg, title),
+            Long("title-no-link") => parse_flag!(arg, title_no_link),
+            Long("json") => parse_flag!(arg, json),
+            Long("all") => parse_flag!(arg, all),
+            Long("manifest-path") => parse_opt!(parser, arg, manifest_path),
+            Long("version-format") => parse_opt!(parser, arg, version_format),
+            Long("prefix-format" | "prefix") => parse_opt!(parser, arg, prefix_format),
+            Short('h') | Long("help") => {
+                print!("{USAGE}");
+                return Ok(());
+            }
+            Short('V') | Long("version") => {
+                println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+                return Ok(());
+            }
+            Value(val) if release.is_none() => release = Some(val.parse()?),
+            _ => return Err(arg.unexpected().into()),
+        }
+    }
+
+    if title && title_no_link {
+        crate::conflicts("--title", "--title-no-link")?;
+    }
+    if all {
+        if title {
+            crate::conflicts("--all", "--title")?;
+        }
+        if title_no_link {
+            crate::conflicts("--all", "--title-no-link")?;
+        }
+        if json {
+            crate::conflicts("--all", "--json")?;
+        }
+        if release.is_some() {
+            bail!("--all may not be used together with [VERSION]");
+        }
+    }
+
+    let manifest_path = match manifest_path {
+        Some(manifest_path) => manifest_path,
+        None => {
+            let cwd = env::current_dir()?;
+            let Some(manifest_path) =
+                cwd.ancestors().map(|dir| dir.join("Cargo.toml")).find(|path| path.is_file())
+            else {
+                bail!("could not find Cargo.toml in `{}` or any parent directory", cwd.display())
+            };
+            manifest_path
+        }
+    };
+    let path = manifest_path.with_file_name("CHANGELOG.md");
+    if !path.is_file() {
+        bail!("no CHANGELOG.md was found next to `{}`", manifest_path.display());
+    }
+    if release.is_none() && !all && !json {
+        release = Some(workspace::package_version(&manifest_path)?);
+    }
+
+    crate::run(&crate::Args {
+        path,
+        release,
+        title,
+        title_no_link,
+        json,
+        list: all,
+        version_format,
+        prefix_format,
+        ..Default::default()
+    })
+}
@@ -0,0 +1,317 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, BufWriter, Write as _},
+    path::{Path, PathBuf},
+};
+
+use lexopt::Arg::{Long, Short};
+
+use crate::{
+    Result, entries,
+    version::{self, Bump},
+    workspace,
+};
+
+static USAGE: &str = "parse-changelog changeset
+
+Write release sections from Changesets (https://github.com/changesets/changesets).
+
+Reads changeset files (markdown files with YAML front matter that maps package
+names to bump types) in the changeset directory, computes the new version of each
+package by bumping the latest version in its changelog, and renders a new release
+section with 'Major Changes', 'Minor Changes', and 'Patch Changes' subsections.
+
+By default, the new release sections are printed to standard output. With
+--write, they are inserted above the latest release in each changelog.
+
+The changelog of each package defaults to CHANGELOG.md in the directory that
+contains package.json of the package. Packages are the root package and
+packages matched by `workspaces` in package.json in the parent directory of the
+changeset directory.
+
+USAGE:
+    parse-changelog changeset [OPTIONS]
+
+OPTIONS:
+        --changelog <PACKAGE>=<PATH>  Path to the changelog file of the package (can be specified
+                                      multiple times)
+        --dir <DIR>                   Path to the changeset directory [default: .changeset]
+        --date <DATE>                 Release date to be included in the titles
+        --write                       Insert the new release sections into the changelogs
+        --delete                      Delete consumed changesets (requires --write)
+        --version-format <PATTERN>    Specify version format
+        --prefix-format <PATTERN>     Specify prefix format [aliases: prefix]
+    -h, --help                        Print help information
+";
+
+struct Args {
+    changelogs: Vec<(String, PathBuf)>,
+    dir: Option<PathBuf>,
+    date: Option<String>,
+    write: bool,
+    delete: bool,
+    version_format: Option<String>,
+    prefix_format: Option<String>,
+}
+
+impl Args {
+    fn parse(mut parser: lexopt::Parser) -> Result<Option<Self>> {
+        let mut changelogs = vec![];
+        let mut dir = None;
+        let mut date = None;
+        let mut write = false;
+        let mut delete = false;
+        let mut version_format = None;
+        let mut prefix_format = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("changelog") => {
+                    let val: String = lexopt::ValueExt::parse(&parser.value()?)?;
+                    let Some((package, path)) = val.split_once('=') else {
+                        bail!("--changelog must be in the form <PACKAGE>=<PATH>, but got '{val}'")
+                    };
+                    changelogs.push((package.to_owned(), path.into()));
+                }
+                Long("dir") => parse_opt!(parser, arg, dir),
+                Long("date") => parse_opt!(parser, arg, date),
+                Long("write") => parse_flag!(arg, write),
+                Long("delete") => parse_flag!(arg, delete),
+                Long("version-format") => parse_opt!(parser, arg, version_format),
+                Long("prefix-format" | "prefix") => parse_opt!(parser, arg, prefix_format),
+                Short('h') | Long("help") => {
+                    print!("{USAGE}");
+                    return Ok(None);
+                }
+                _ => return Err(arg.unexpected().into()),
+            }
+        }
+
+        if delete && !write {
+            crate::requires("--delete", "--write")?;
+        }
+
+        Ok(Some(Self { changelogs, dir, date, write, delete, version_format, prefix_format }))
+    }
+}
+
+/// A parsed changeset file.
+struct Changeset {
+    releases: Vec<(String, Bump)>,
+    summary: String,
+}
+
+impl Changeset {
+    fn parse(text: &str, path: &Path) -> Result<Option<Self>> {
+        let mut lines = text.lines();
+        if lines.next().map(str::trim_end) != Some("---") {
+            // Not a changeset (e.g., README.md).
+            return Ok(None);
+        }
+        let mut releases = vec![];
+        let mut closed = false;
+        for line in lines.by_ref() {
+            let line = line.trim();
+            if line == "---" {
+                closed = true;
+                break;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((package, bump)) = line.rsplit_once(':') else {
+                bail!("invalid line '{line}' in front matter of `{}`", path.display())
+            };
+            let package = package.trim().trim_matches(|c| c == '"' || c == '\'');
+            let bump = bump.trim().trim_matches(|c| c == '"' || c == '\'');
+            let bump = bump.parse().map_err(|e| format!("{e} in `{}`", path.display()))?;
+            releases.push((package.to_owned(), bump));
+        }
+        if !closed {
+            bail!("unterminated front matter in `{}`", path.display());
+        }
+        let summary = lines.collect::<Vec<_>>().join("\n").trim().to_owned();
+        Ok(Some(Self { releases, summary }))
+    }
+}
+
+/// Reads changesets in the given directory, and returns them with their paths.
+fn read_changesets(dir: &Path) -> Result<Vec<(Changeset, PathBuf)>> {
+    let read_dir = fs::read_dir(dir)
+        .map_err(|e| format!("failed to read directory `{}`: {e}", dir.display()))?;
+    let mut paths = vec![];
+    for entry in read_dir {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|e| e == "md") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    let mut changesets = Vec::with_capacity(paths.len());
+    for path in paths {
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read from file `{}`: {e}", path.display()))?;
+        if let Some(changeset) = Changeset::parse(&text, &path)? {
+            changesets.push((changeset, path));
+        }
+    }
+    Ok(changesets)
+}
+
+fn read_package_json(path: &Path) -> Result<serde_json::Value> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read from file `{}`: {e}", path.display()))?;
+    Ok(serde_json::from_str(&text)
+        .map_err(|e| format!("failed to parse `{}`: {e}", path.display()))?)
+}
+
+/// Returns the default changelog of each package in the npm workspace at
+/// `root`: CHANGELOG.md in the directory that contains package.json of the
+/// package.
+fn default_changelogs(root: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let manifest_path = root.join("package.json");
+    if !manifest_path.is_file() {
+        return Ok(vec![]);
+    }
+    let manifest = read_package_json(&manifest_path)?;
+    let mut changelogs = vec![];
+    if let Some(name) = manifest.get("name").and_then(serde_json::Value::as_str) {
+        changelogs.push((name.to_owned(), root.join("CHANGELOG.md")));
+    }
+    // "workspaces": [...] or "workspaces": { "packages": [...] }
+    let workspaces = manifest.get("workspaces").map(|w| w.get("packages").unwrap_or(w));
+    let patterns = workspaces.and_then(serde_json::Value::as_array).map_or(&[][..], |a| a);
+    let mut dirs: Vec<PathBuf> = vec![];
+    for pattern in patterns.iter().filter_map(serde_json::Value::as_str) {
+        // Negated patterns (e.g., `!packages/private`) are not supported.
+        if pattern.starts_with('!') {
+            continue;
+        }
+        for dir in workspace::glob(root, pattern.trim_end_matches('/'), "package.json") {
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+    }
+    for dir in dirs {
+        let manifest = read_package_json(&dir.join("package.json"))?;
+        if let Some(name) = manifest.get("name").and_then(serde_json::Value::as_str) {
+            changelogs.push((name.to_owned(), dir.join("CHANGELOG.md")));
+        }
+    }
+    Ok(changelogs)
+}
+
+/// Pending changes of a package.
+#[derive(Default)]
+struct PackageChanges {
+    bump: Option<Bump>,
+    summaries: BTreeMap<Bump, Vec<String>>,
+}
+
+pub(crate) fn run(parser: lexopt::Parser) -> Result<()> {
+    let Some(args) = Args::parse(parser)? else { return Ok(()) };
+
+    let dir = args.dir.clone().unwrap_or_else(|| PathBuf::from(".changeset"));
+    let changesets = read_changesets(&dir)?;
+    if changesets.iter().all(|(c, _)| c.releases.is_empty()) {
+        bail!("no changesets were found in `{}`", dir.display());
+    }
+
+    let mut changelogs = args.changelogs.clone();
+    let mut has_defaults = false;
+    let mut packages: Vec<(&str, PackageChanges)> = vec![];
+    for (changeset, path) in &changesets {
+        for (package, bump) in &changeset.releases {
+            if !has_defaults && !changelogs.iter().any(|(p, _)| p == package) {
+                // Changelogs specified by --changelog take precedence.
+                has_defaults = true;
+                let root = dir.parent().unwrap_or_else(|| Path::new(""));
+                for (package, path) in default_changelogs(root)? {
+                    if !changelogs.iter().any(|(p, _)| *p == package) {
+                        changelogs.push((package, path));
+                    }
+                }
+            }
+            if !changelogs.iter().any(|(p, _)| p == package) {
+                bail!(
+                    "no changelog found for package '{package}' in `{}`; use --changelog {package}=<PATH>",
+                    path.display()
+                );
+            }
+            let i = match packages.iter().position(|(p, _)| p == package) {
+                Some(i) => i,
+                None => {
+                    packages.push((package, PackageChanges::default()));
+                    packages.len() - 1
+                }
+            };
+            let changes = &mut packages[i].1;
+            changes.bump = changes.bump.max(Some(*bump));
+            if !changeset.summary.is_empty() {
+                changes.summaries.entry(*bump).or_default().push(changeset.summary.clone());
+            }
+        }
+    }
+
+    let parser = crate::new_parser(args.version_format.as_deref(), args.prefix_format.as_deref())?;
+    let mut stdout = BufWriter::new(io::stdout().lock()); // Buffered because it is written many times.
+    // Changelogs are written after all of them are rendered, so that an error
+    // in one of them doesn't leave the others half-updated.
+    let mut outputs = vec![];
+    for (package, changes) in &packages {
+        let path = &changelogs.iter().find(|(p, _)| p == package).unwrap().1;
+        let text = crate::read_changelog(path)?;
+        let Some(latest) = parser.parse_iter(&text).find(|r| r.version != "Unreleased") else {
+            bail!("no release was found in {}", path.display());
+        };
+        let bump = changes.bump.unwrap();
+        let Some(version) = version::bump(latest.version, bump) else {
+            bail!(
+                "failed to bump version '{}' in {}: not a MAJOR.MINOR.PATCH version",
+                latest.version,
+                path.display()
+            );
+        };
+        let title = match &args.date {
+            Some(date) => format!("{version} - {date}"),
+            None => version.clone(),
+        };
+        let (new, section) = entries::insert_release(&parser, &text, &version, &title, |level| {
+            entries::render_sections(
+                level,
+                [
+                    (Bump::Major, "Major Changes"),
+                    (Bump::Minor, "Minor Changes"),
+                    (Bump::Patch, "Patch Changes"),
+                ]
+                .into_iter()
+                .filter_map(|(bump, heading)| Some((heading, &changes.summaries.get(&bump)?[..]))),
+            )
+        })?;
+        if args.write {
+            outputs.push((path, new));
+        } else {
+            writeln!(stdout, "{package}: {} -> {version} ({})", latest.version, path.display())?;
+            writeln!(stdout)?;
+            writeln!(stdout, "{}", new[section].trim_end())?;
+            writeln!(stdout)?;
+        }
+    }
+    stdout.flush()?;
+
+    for (path, new) in outputs {
+        fs::write(path, new)
+            .map_err(|e| format!("failed to write to file `{}`: {e}", path.display()))?;
+    }
+    if args.delete {
+        for (_, path) in &changesets {
+            fs::remove_file(path)
+                .map_err(|e| format!("failed to remove file `{}`: {e}", path.display()))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use lexopt::Arg::{Long, Short, Value};
+use regex::Regex;
+
+use crate::{Result, workspace};
+
+static USAGE: &str = "parse-changelog check-version
+
+Check that the changelog has a release note for the version in the manifest.
+
+Reads the version from the manifest (Cargo.toml, package.json, or pyproject.toml)
+or from a file with a regular expression, and exits with an error unless the
+changelog has a non-empty release note for that version. This is intended to be
+used as a pre-publish hook.
+
+USAGE:
+    parse-changelog check-version [OPTIONS] <PATH>
+
+ARGS:
+    <PATH>    Path to the changelog file (use '-' for standard input, or 'git:<REV>:<PATH>' to
+              read the file at the specified git revision)
+
+OPTIONS:
+        --manifest <PATH>             Path to the manifest (Cargo.toml, package.json, or
+                                      pyproject.toml) [default: the first of them found in the
+                                      directory of the changelog]
+        --version-file <PATH>         Path to the file to read the version from (requires
+                                      --version-regex)
+        --version-regex <PATTERN>     Regular expression to extract the version from
+                                      --version-file; the version is the text captured by the
+                                      group named 'version' or the first group
+        --allow-unreleased            Also succeed if there is no release note for the version
+                                      but there is an 'Unreleased' section
+        --version-format <PATTERN>    Specify version format
+        --prefix-format <PATTERN>     Specify prefix format [aliases: prefix]
+    -h, --help                        Print help information
+";
+
+const MANIFESTS: [&str; 3] = ["Cargo.toml", "package.json", "pyproject.toml"];
+
+struct Args {
+    path: PathBuf,
+    manifest: Option<PathBuf>,
+    version_file: Option<PathBuf>,
+    version_regex: Option<String>,
+    allow_unreleased: bool,
+    version_format: Option<String>,
+    prefix_format: Option<String>,
+}
+
+impl Args {
+    fn parse(mut parser: lexopt::Parser) -> Result<Option<Self>> {
+        let mut path = None;
+        let mut manifest = None;
+        let mut version_file = None;
+        let mut version_regex = None;
+        let mut allow_unreleased = false;
+        let mut version_format = None;
+        let mut prefix_format = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("manifest") => parse_opt!(parser, arg, manifest),
+                Long("version-file") => parse_opt!(parser, arg, version_file),
+                Long("version-regex") => parse_opt!(parser, arg, version_regex),
+                Long("allow-unreleased") => parse_flag!(arg, allow_unreleased),
+                Long("version-format") => parse_opt!(parser, arg, version_format),
+                Long("prefix-format" | "prefix") => parse_opt!(parser, arg, prefix_format),
+                Short('h') | Long("help") => {
+                    print!("{USAGE}");
+                    return Ok(None);
+                }
+                Value(val) if path.is_none() => path = Some(val.into()),
+                _ => return Err(arg.unexpected().into()),
+            }
+        }
+
+        let Some(path) = path else { bail!("no changelog path specified") };
+        if manifest.is_some() && version_file.is_some() {
+            crate::conflicts("--manifest", "--version-file")?;
+        }
+        if version_file.is_some() && version_regex.is_none() {
+            crate::requires("--version-file", "--version-regex")?;
+        }
+        if version_regex.is_some() && version_file.is_none() {
+            crate::requires("--version-regex", "--version-file")?;
+        }
+
+        Ok(Some(Self {
+            path,
+            manifest,
+            version_file,
+            version_regex,
+            allow_unreleased,
+            version_format,
+            prefix_format,
+        }))
+    }
+}
+
+/// Reads the version from the given manifest. The format is determined by
+/// the file name.
+fn manifest_version(path: &Path) -> Result<String> {
+    match path.file_name().and_then(|n| n.to_str()) {
+        Some("Cargo.toml") => workspace::package_version(path),
+        Some("package.json") => {
+            let text = read_to_string(path)?;
+            let json: serde_json::Value = serde_json::from_str(&text)
+                .map_err(|e| format!("failed to parse `{}`: {e}", path.display()))?;
+            match json.get("version").and_then(serde_json::Value::as_str) {
+                Some(version) => Ok(version.to_owned()),
+                None => bail!("no `version` in `{}`", path.display()),
+            }
+        }
+        Some("pyproject.toml") => {
+            let text = read_to_string(path)?;
+            let toml: toml::Table =
+                text.parse().map_err(|e| format!("failed to parse `{}`: {e}", path.display()))?;
+            let project = toml.get("project");
+            if let Some(version) = project.and_then(|p| p.get("version")?.as_str()) {
+                return Ok(version.to_owned());
+            }
+            let dynamic = project
+                .and_then(|p| p.get("dynamic")?.as_array())
+                .is_some_and(|d| d.iter().any(|v| v.as_str() == Some("version")));
+            if dynamic {
+                bail!(
+                    "`project.version` in `{}` is dynamic; use --version-file and --version-regex instead",
+                    path.display()
+                );
+            }
+            // Poetry before 2.0 uses tool.poetry table instead of project table.
+            match toml.get("tool").and_then(|t| t.get("poetry")?.get("version")?.as_str()) {
+                Some(version) => Ok(version.to_owned()),
+                None => bail!("no `project.version` in `{}`", path.display()),
+            }
+        }
+        _ => bail!(
+            "unsupported manifest `{}` (expected Cargo.toml, package.json, or pyproject.toml); \
+             use --version-file and --version-regex instead",
+            path.display()
+        ),
+    }
+}
+
+/// Reads the version from the given file by using the given regular expression.
+fn file_version(path: &Path, regex: &str) -> Result<String> {
+    let regex = Regex::new(regex).map_err(|e| format!("invalid --version-regex: {e}"))?;
+    let text = read_to_string(path)?;
+    let Some(caps) = regex.captures(&text) else {
+        bail!("--version-regex did not match `{}`", path.display())
+    };
+    match caps.name("version").or_else(|| caps.get(1)) {
+        Some(version) => Ok(version.as_str().to_owned()),
+        None => bail!("--version-regex must have a capture group for the version"),
+    }
+}
+
+fn read_to_string(path: &Path) -> Result<String> {
+    Ok(fs::read_to_string(path)
+        .map_err(|e| format!("failed to read from file `{}`: {e}", path.display()))?)
+}
+
+pub(crate) fn run(parser: lexopt::Parser) -> Result<()> {
+    let Some(args) = Args::parse(parser)? else { return Ok(()) };
+
+    let (version, source) = if let Some(path) = &args.version_file {
+        (file_version(path, args.version_regex.as_deref().unwrap())?, path.clone())
+    } else {
+        let manifest = match &args.manifest {
+            Some(manifest) => manifest.clone(),
+            None => {
+                // The manifest is searched in the current directory if the
+                // changelog is read from standard input or git.
+                let dir = if args.path.as_os_str() == "-" || crate::git_path(&args.path).is_some() {
+                    Path::new("")
+                } else {
+                    args.path.parent().unwrap_or_else(|| Path::new(""))
+                };
+                let Some(manifest) =
+                    MANIFESTS.iter().map(|m| dir.join(m)).find(|path| path.is_file())
+                else {
+                    bail!(
+                        "no manifest (Cargo.toml, package.json, or pyproject.toml) was found in {}; \
+                         use --manifest to specify it",
+                        if dir.as_os_str().is_empty() {
+                            "current directory".to_owned()
+                        } else {
+                            format!("`{}`", dir.display())
+                        }
+                    )
+                };
+                manifest
+            }
+        };
+        (manifest_version(&manifest)?, manifest)
+    };
+
+    let parser = crate::new_parser(args.version_format.as_deref(), args.prefix_format.as_deref())?;
+    let text = crate::read_changelog(&args.path)?;
+    let changelog = crate::parse(&parser, &text, &args.path)?;
+    let path = crate::path_for_msg(&args.path);
+    match changelog.get(&*version) {
+        Some(release) if release.notes.is_empty() => {
+            bail!(
+                "release note for '{version}' (from `{}`) in {} is empty",
+                source.display(),
+                path.display()
+            )
+        }
+        Some(_) => Ok(()),
+        None if args.allow_unreleased && changelog.contains_key("Unreleased") => Ok(()),
+        None => {
+            bail!(
+                "not found release note for '{version}' (from `{}`) in {}{}",
+                source.display(),
+                path.display(),
+                if args.allow_unreleased { " and no 'Unreleased' section" } else { "" }
+            )
+        }
+    }
+}
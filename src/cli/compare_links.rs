@@ -0,0 +1,309 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{
+    fs,
+    io::{self, Write as _},
+    ops::Range,
+    path::PathBuf,
+};
+
+use lexopt::Arg::{Long, Short, Value};
+
+use crate::Result;
+
+static USAGE: &str = "parse-changelog compare-links
+
+Maintain link reference definitions of releases at the end of the changelog
+(e.g., `[1.2.0]: https://github.com/owner/repo/compare/v1.1.0...v1.2.0`).
+
+The expected definition of each release is computed from the compare URL
+template and the previous release in the changelog. 'Unreleased' is compared
+with HEAD. The oldest release uses the --first-url template, and is ignored if
+it is not specified. Releases whose titles are not links (e.g., `## 1.2.0`
+instead of `## [1.2.0]`) are ignored.
+
+By default, the expected definitions are printed to standard output. With
+--write, missing definitions are added and stale ones are fixed, leaving the
+rest of the changelog untouched. With --check, mismatches are reported, and the
+command exits with an error if any are found.
+
+USAGE:
+    parse-changelog compare-links [OPTIONS] --compare-url <TEMPLATE> <PATH>
+
+ARGS:
+    <PATH>    Path to the changelog file
+
+OPTIONS:
+        --compare-url <TEMPLATE>      URL template of links to compare two releases ('{previous}'
+                                      and '{current}' are replaced with tags of the previous and
+                                      current releases; e.g.,
+                                      https://github.com/owner/repo/compare/{previous}...{current})
+        --first-url <TEMPLATE>        URL template of the link of the oldest release ('{current}'
+                                      is replaced with the tag of the release; e.g.,
+                                      https://github.com/owner/repo/releases/tag/{current})
+        --tag-prefix <PREFIX>         Prefix of tags [default: v]
+        --write                       Add missing definitions and fix stale ones in the changelog
+        --check                       Exit with an error if any definition is missing or stale
+        --version-format <PATTERN>    Specify version format
+        --prefix-format <PATTERN>     Specify prefix format [aliases: prefix]
+    -h, --help                        Print help information
+";
+
+struct Args {
+    path: PathBuf,
+    compare_url: String,
+    first_url: Option<String>,
+    tag_prefix: Option<String>,
+    write: bool,
+    check: bool,
+    version_format: Option<String>,
+    prefix_format: Option<String>,
+}
+
+impl Args {
+    fn parse(mut parser: lexopt::Parser) -> Result<Option<Self>> {
+        let mut path: Option<PathBuf> = None;
+        let mut compare_url = None;
+        let mut first_url = None;
+        let mut tag_prefix = None;
+        let mut write = false;
+        let mut check = false;
+        let mut version_format = None;
+        let mut prefix_format = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("compare-url") => parse_opt!(parser, arg, compare_url),
+                Long("first-url") => parse_opt!(parser, arg, first_url),
+                Long("tag-prefix") => parse_opt!(parser, arg, tag_prefix),
+                Long("write") => parse_flag!(arg, write),
+                Long("check") => parse_flag!(arg, check),
+                Long("version-format") => parse_opt!(parser, arg, version_format),
+                Long("prefix-format" | "prefix") => parse_opt!(parser, arg, prefix_format),
+                Short('h') | Long("help") => {
+                    print!("{USAGE}");
+                    return Ok(None);
+                }
+                Value(val) if path.is_none() => path = Some(val.into()),
+                _ => return Err(arg.unexpected().into()),
+            }
+        }
+
+        let Some(path) = path else { bail!("no changelog path specified") };
+        let Some(compare_url) = compare_url else { bail!("no --compare-url specified") };
+        if write {
+            if check {
+                crate::conflicts("--write", "--check")?;
+            }
+            if path.as_os_str() == "-" || crate::git_path(&path).is_some() {
+                bail!("--write requires a path to a file in the working tree");
+            }
+        }
+
+        Ok(Some(Self {
+            path,
+            compare_url,
+            first_url,
+            tag_prefix,
+            write,
+            check,
+            version_format,
+            prefix_format,
+        }))
+    }
+}
+
+/// A link reference definition in the changelog.
+struct Definition {
+    /// The index of the line.
+    line: usize,
+    /// The range of the destination in the line.
+    url: Range<usize>,
+}
+
+/// Returns the label of the link reference definition used by the title of
+/// the release (e.g., `v1.0.0` in `## [v1.0.0] - 2024-01-01`), or `None` if
+/// the title is not a link or is an inline link.
+fn title_label(title: &str) -> Option<&str> {
+    let (text, rest) = title.strip_prefix('[')?.split_once(']')?;
+    if rest.starts_with('(') {
+        return None;
+    }
+    // [text][label]
+    match rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+        Some((label, _)) if !label.is_empty() => Some(label),
+        _ => Some(text),
+    }
+}
+
+pub(crate) fn run(parser: lexopt::Parser) -> Result<()> {
+    let Some(args) = Args::parse(parser)? else { return Ok(()) };
+
+    let parser = crate::new_parser(args.version_format.as_deref(), args.prefix_format.as_deref())?;
+    let text = crate::read_changelog(&args.path)?;
+    let changelog = crate::parse(&parser, &text, &args.path)?;
+    let tag_prefix = args.tag_prefix.as_deref().unwrap_or("v");
+
+    // Compute expected definitions, from newest to oldest.
+    let versions: Vec<&str> = changelog.keys().copied().collect();
+    let tag = |version: &str| {
+        if version == "Unreleased" { "HEAD".to_owned() } else { format!("{tag_prefix}{version}") }
+    };
+    let mut expected = Vec::with_capacity(versions.len());
+    for (i, &version) in versions.iter().enumerate() {
+        let Some(label) = title_label(changelog[version].title) else { continue };
+        let url = match versions.get(i + 1) {
+            Some(&previous) => args
+                .compare_url
+                .replace("{previous}", &tag(previous))
+                .replace("{current}", &tag(version)),
+            None => match &args.first_url {
+                Some(first_url) => first_url.replace("{current}", &tag(version)),
+                None => continue,
+            },
+        };
+        expected.push((version, label, url));
+    }
+
+    if !args.write && !args.check {
+        let mut stdout = io::stdout().lock();
+        for (_, label, url) in &expected {
+            writeln!(stdout, "[{label}]: {url}")?;
+        }
+        stdout.flush()?;
+        return Ok(());
+    }
+
+    // Collect existing definitions outside of code blocks and comments.
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let mut line_starts = Vec::with_capacity(lines.len());
+    let mut line_start = 0;
+    for line in &lines {
+        line_starts.push(line_start);
+        line_start += line.len();
+    }
+    let mut definitions: Vec<(String, Definition)> = vec![];
+    for definition in parse_changelog::link_definitions(&text) {
+        // Labels are case-insensitive, and the first definition takes precedence.
+        let label = definition.label.to_lowercase();
+        if definitions.iter().any(|(l, _)| *l == label) {
+            continue;
+        }
+        let Some(start) = crate::subslice_offset(&text, definition.destination) else { continue };
+        let line = line_starts.partition_point(|&s| s <= start) - 1;
+        let start = start - line_starts[line];
+        let url = start..start + definition.destination.len();
+        definitions.push((label, Definition { line, url }));
+    }
+    let find = |label: &str| {
+        let label = label.to_lowercase();
+        definitions.iter().find(|(l, _)| *l == label).map(|(_, d)| d)
+    };
+
+    let mut stdout = io::stdout().lock();
+    let mut mismatches = 0;
+    // Replacements of destinations, and lines inserted before or after each line.
+    let mut replaced: Vec<(usize, Range<usize>, &str)> = vec![];
+    let mut before: Vec<(usize, String)> = vec![];
+    let mut after: Vec<(usize, String)> = vec![];
+    let mut at_end: Vec<(&str, &str)> = vec![];
+    // The line of the definition of the newer release.
+    let mut prev_line = None;
+    for (i, (version, label, url)) in expected.iter().enumerate() {
+        if let Some(definition) = find(label) {
+            let found = &lines[definition.line][definition.url.clone()];
+            if found != url {
+                mismatches += 1;
+                if args.check {
+                    writeln!(
+                        stdout,
+                        "{version}: stale link reference definition (expected '{url}', found '{found}')"
+                    )?;
+                }
+                replaced.push((definition.line, definition.url.clone(), url));
+            }
+            prev_line = Some(definition.line);
+            continue;
+        }
+        mismatches += 1;
+        let new = format!("[{label}]: {url}\n");
+        if args.check {
+            writeln!(
+                stdout,
+                "{version}: missing link reference definition (expected '{}')",
+                new.trim_end()
+            )?;
+        }
+        if let Some(line) = prev_line {
+            after.push((line, new));
+        } else if let Some(definition) = expected[i + 1..].iter().find_map(|(_, l, _)| find(l)) {
+            before.push((definition.line, new));
+        } else {
+            at_end.push((label, url.as_str()));
+        }
+    }
+    stdout.flush()?;
+
+    if args.check {
+        if mismatches != 0 {
+            bail!(
+                "found {mismatches} mismatch(es) in link reference definitions of {}",
+                crate::path_for_msg(&args.path).display()
+            );
+        }
+        return Ok(());
+    }
+    if mismatches == 0 {
+        return Ok(());
+    }
+
+    let mut new =
+        String::with_capacity(text.len() + 64 * (before.len() + after.len() + at_end.len()));
+    for (i, line) in lines.iter().enumerate() {
+        for (_, s) in before.iter().filter(|(l, _)| *l == i) {
+            new.push_str(s);
+        }
+        match replaced.iter().find(|(l, ..)| *l == i) {
+            Some((_, range, url)) => {
+                new.push_str(&line[..range.start]);
+                new.push_str(url);
+                new.push_str(&line[range.end..]);
+            }
+            None => new.push_str(line),
+        }
+        if !line.ends_with('\n') && after.iter().any(|(l, _)| *l == i) {
+            new.push('\n');
+        }
+        for (_, s) in after.iter().filter(|(l, _)| *l == i) {
+            new.push_str(s);
+        }
+    }
+    if let Some(&(last_label, last_url)) = at_end.last() {
+        if !new.is_empty() && !new.ends_with('\n') {
+            new.push('\n');
+        }
+        if !new.is_empty() && !new.ends_with("\n\n") {
+            new.push('\n');
+        }
+        for (label, url) in &at_end {
+            new.push('[');
+            new.push_str(label);
+            new.push_str("]: ");
+            new.push_str(url);
+            new.push('\n');
+        }
+        // Definitions appended after an unclosed code block or comment are
+        // not recognized as definitions.
+        let last = parse_changelog::link_definitions(&new).pop();
+        if last.is_none_or(|d| d.label != last_label || d.destination != last_url) {
+            bail!(
+                "cannot add link reference definitions at the end of {} because it ends inside \
+                 a code block or a comment",
+                crate::path_for_msg(&args.path).display()
+            );
+        }
+    }
+    fs::write(&args.path, new)
+        .map_err(|e| format!("failed to write to file `{}`: {e}", args.path.display()))?;
+    Ok(())
+}
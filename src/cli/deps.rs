@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    env, fs,
+    io::{self, BufWriter, Write as _},
+    path::{Path, PathBuf},
+};
+
+use lexopt::Arg::{Long, Short, Value};
+use toml::{Table, Value as TomlValue};
+
+use crate::{Result, version};
+
+static USAGE: &str = "parse-changelog deps
+
+Output changelogs of dependencies updated between two Cargo.lock files.
+
+For each crate from a registry whose version was updated, finds its changelog
+(CHANGELOG.md, RELEASES.md, or CHANGES.md) in the unpacked sources of the new
+version under $CARGO_HOME/registry/src, and outputs the release notes for the
+versions after the old version up to the new version. This works fully offline
+with whatever is already downloaded (e.g., by `cargo fetch`).
+
+USAGE:
+    parse-changelog deps [OPTIONS] <OLD> <NEW>
+
+ARGS:
+    <OLD>    Path to the old Cargo.lock (use 'git:<REV>:<PATH>' to read the file at the
+             specified git revision)
+    <NEW>    Path to the new Cargo.lock (use 'git:<REV>:<PATH>' to read the file at the
+             specified git revision)
+
+OPTIONS:
+        --version-format <PATTERN>    Specify version format
+        --prefix-format <PATTERN>     Specify prefix format [aliases: prefix]
+    -h, --help                        Print help information
+";
+
+/// Changelog file names, in the order of preference.
+const CHANGELOGS: [&str; 3] = ["CHANGELOG.md", "RELEASES.md", "CHANGES.md"];
+
+struct Args {
+    old: PathBuf,
+    new: PathBuf,
+    version_format: Option<String>,
+    prefix_format: Option<String>,
+}
+
+impl Args {
+    fn parse(mut parser: lexopt::Parser) -> Result<Option<Self>> {
+        let mut old: Option<PathBuf> = None;
+        let mut new: Option<PathBuf> = None;
+        let mut version_format = None;
+        let mut prefix_format = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("version-format") => parse_opt!(parser, arg, version_format),
+                Long("prefix-format" | "prefix") => parse_opt!(parser, arg, prefix_format),
+                Short('h') | Long("help") => {
+                    print!("{USAGE}");
+                    return Ok(None);
+                }
+                Value(val) if old.is_none() => old = Some(val.into()),
+                Value(val) if new.is_none() => new = Some(val.into()),
+                _ => return Err(arg.unexpected().into()),
+            }
+        }
+
+        let (Some(old), Some(new)) = (old, new) else {
+            bail!("two Cargo.lock paths must be specified (<OLD> <NEW>)")
+        };
+
+        Ok(Some(Self { old, new, version_format, prefix_format }))
+    }
+}
+
+/// Returns versions of crates from registries in the given Cargo.lock, keyed by
+/// crate name.
+fn registry_packages(text: &str, path: &Path) -> Result<BTreeMap<String, Vec<String>>> {
+    let lockfile: Table =
+        text.parse().map_err(|e| format!("failed to parse `{}`: {e}", path.display()))?;
+    let mut packages: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let Some(TomlValue::Array(array)) = lockfile.get("package") else { return Ok(packages) };
+    for package in array {
+        let (Some(name), Some(version), Some(source)) = (
+            package.get("name").and_then(TomlValue::as_str),
+            package.get("version").and_then(TomlValue::as_str),
+            package.get("source").and_then(TomlValue::as_str),
+        ) else {
+            // Path dependencies have no source.
+            continue;
+        };
+        if source.starts_with("registry+") || source.starts_with("sparse+") {
+            packages.entry(name.to_owned()).or_default().push(version.to_owned());
+        }
+    }
+    Ok(packages)
+}
+
+/// Returns pairs of old and new versions of the crate.
+///
+/// Each new version is paired with the latest old version that is older than
+/// it. New versions that have no such old version (i.e., newly added or
+/// downgraded) are ignored.
+fn updates<'a>(old: &'a [String], new: &'a [String]) -> Vec<(&'a str, &'a str)> {
+    let removed: Vec<&str> = old.iter().filter(|v| !new.contains(v)).map(String::as_str).collect();
+    let mut updates = vec![];
+    for new in new.iter().filter(|v| !old.contains(v)) {
+        let old = removed
+            .iter()
+            .filter(|old| version::cmp(old, new) == Ordering::Less)
+            .max_by(|a, b| version::cmp(a, b));
+        if let Some(old) = old {
+            updates.push((*old, new.as_str()));
+        }
+    }
+    updates
+}
+
+fn cargo_home() -> Option<PathBuf> {
+    if let Some(cargo_home) = env::var_os("CARGO_HOME") {
+        return Some(cargo_home.into());
+    }
+    let home = env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" })?;
+    Some(Path::new(&home).join(".cargo"))
+}
+
+/// Returns the path to the unpacked sources of the given crate in the
+/// registry cache.
+fn find_sources(registry_src: &Path, name: &str, version: &str) -> Option<PathBuf> {
+    let dir_name = format!("{name}-{version}");
+    let mut registries: Vec<_> =
+        fs::read_dir(registry_src).ok()?.filter_map(|e| Some(e.ok()?.path())).collect();
+    registries.sort();
+    registries.into_iter().map(|registry| registry.join(&dir_name)).find(|dir| dir.is_dir())
+}
+
+pub(crate) fn run(parser: lexopt::Parser) -> Result<()> {
+    let Some(args) = Args::parse(parser)? else { return Ok(()) };
+
+    let parser = crate::new_parser(args.version_format.as_deref(), args.prefix_format.as_deref())?;
+    let old = registry_packages(&crate::read_changelog(&args.old)?, &args.old)?;
+    let new = registry_packages(&crate::read_changelog(&args.new)?, &args.new)?;
+    let Some(cargo_home) = cargo_home() else {
+        bail!("failed to determine cargo home directory; set CARGO_HOME environment variable")
+    };
+    let registry_src = cargo_home.join("registry/src");
+
+    let mut stdout = BufWriter::new(io::stdout().lock()); // Buffered because it is written many times.
+    let mut first = true;
+    for (name, new_versions) in &new {
+        let Some(old_versions) = old.get(name) else { continue };
+        for (old_version, new_version) in updates(old_versions, new_versions) {
+            if !first {
+                writeln!(stdout)?;
+            }
+            first = false;
+            writeln!(stdout, "## {name} {old_version} -> {new_version}")?;
+            writeln!(stdout)?;
+            let Some(dir) = find_sources(&registry_src, name, new_version) else {
+                writeln!(stdout, "Sources were not found in `{}`.", registry_src.display())?;
+                continue;
+            };
+            let Some(path) = CHANGELOGS.iter().map(|c| dir.join(c)).find(|p| p.is_file()) else {
+                writeln!(stdout, "No changelog was found in `{}`.", dir.display())?;
+                continue;
+            };
+            let text = crate::read_changelog(&path)?;
+            let releases: Vec<_> = parser
+                .parse_iter(&text)
+                .filter(|r| {
+                    version::cmp(r.version, old_version) == Ordering::Greater
+                        && version::cmp(r.version, new_version) != Ordering::Greater
+                })
+                .collect();
+            if releases.is_empty() {
+                writeln!(stdout, "No release notes were found in `{}`.", path.display())?;
+                continue;
+            }
+            for (i, release) in releases.iter().enumerate() {
+                if i != 0 {
+                    writeln!(stdout)?;
+                }
+                writeln!(stdout, "### {}", release.title)?;
+                if !release.notes.is_empty() {
+                    writeln!(stdout)?;
+                    writeln!(stdout, "{}", release.notes)?;
+                }
+            }
+        }
+    }
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,382 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{
+    io::{self, BufWriter, Write as _},
+    path::PathBuf,
+};
+
+use lexopt::Arg::{Long, Short, Value};
+use parse_changelog::{Changelog, Release};
+
+use crate::Result;
+
+static USAGE: &str = "parse-changelog diff
+
+Compare releases in two changelogs.
+
+Reports versions that were added, removed, or modified in <NEW> compared with
+<OLD>, and entries (top-level list items, headings, and paragraphs in the notes)
+that were added to or removed from modified versions. Modifications to released
+versions (other than 'Unreleased') are flagged.
+
+With --check, reports only released versions whose title or notes were
+modified, and exits with an error if any are found. This is useful to prevent
+edits to release notes of already shipped versions, e.g.:
+`parse-changelog diff --check git:origin/main:CHANGELOG.md CHANGELOG.md`
+
+USAGE:
+    parse-changelog diff [OPTIONS] <OLD> <NEW>
+
+ARGS:
+    <OLD>    Path to the old changelog file (use '-' for standard input, or 'git:<REV>:<PATH>'
+             to read the file at the specified git revision)
+    <NEW>    Path to the new changelog file (same format as <OLD>)
+
+OPTIONS:
+        --json                        Output JSON representation of changes
+        --check                       Exit with an error if any released version was modified
+        --version-format <PATTERN>    Specify version format
+        --prefix-format <PATTERN>     Specify prefix format [aliases: prefix]
+    -h, --help                        Print help information
+";
+
+struct Args {
+    old: PathBuf,
+    new: PathBuf,
+    json: bool,
+    check: bool,
+    version_format: Option<String>,
+    prefix_format: Option<String>,
+}
+
+impl Args {
+    fn parse(mut parser: lexopt::Parser) -> Result<Option<Self>> {
+        let mut old: Option<PathBuf> = None;
+        let mut new: Option<PathBuf> = None;
+        let mut json = false;
+        let mut check = false;
+        let mut version_format = None;
+        let mut prefix_format = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("json") => parse_flag!(arg, json),
+                Long("check") => parse_flag!(arg, check),
+                Long("version-format") => parse_opt!(parser, arg, version_format),
+                Long("prefix-format" | "prefix") => parse_opt!(parser, arg, prefix_format),
+                Short('h') | Long("help") => {
+                    print!("{USAGE}");
+                    return Ok(None);
+                }
+                Value(val) if old.is_none() => old = Some(val.into()),
+                Value(val) if new.is_none() => new = Some(val.into()),
+                _ => return Err(arg.unexpected().into()),
+            }
+        }
+
+        let (Some(old), Some(new)) = (old, new) else {
+            bail!("two changelog paths must be specified")
+        };
+        if old.as_os_str() == "-" && new.as_os_str() == "-" {
+            bail!("standard input may not be used for both <OLD> and <NEW>");
+        }
+        if json && check {
+            crate::conflicts("--json", "--check")?;
+        }
+
+        Ok(Some(Self { old, new, json, check, version_format, prefix_format }))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl ChangeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Modified => "modified",
+        }
+    }
+}
+
+/// A change to a release between two changelogs.
+struct Change<'a> {
+    kind: ChangeKind,
+    version: &'a str,
+    old: Option<&'a Release<'a>>,
+    new: Option<&'a Release<'a>>,
+    /// Entries in the notes of `new` that are not in the notes of `old`.
+    added_entries: Vec<String>,
+    /// Entries in the notes of `old` that are not in the notes of `new`.
+    removed_entries: Vec<String>,
+}
+
+impl<'a> Change<'a> {
+    fn title_changed(&self) -> bool {
+        matches!((self.old, self.new), (Some(old), Some(new)) if old.title != new.title)
+    }
+    fn notes_changed(&self) -> bool {
+        matches!((self.old, self.new), (Some(old), Some(new)) if old.notes != new.notes)
+    }
+    fn released(&self) -> bool {
+        self.version != "Unreleased"
+    }
+    fn new(
+        kind: ChangeKind,
+        version: &'a str,
+        old: Option<&'a Release<'a>>,
+        new: Option<&'a Release<'a>>,
+    ) -> Self {
+        let mut removed_entries = old.map_or_else(Vec::new, |old| entries(old.notes));
+        let mut added_entries = vec![];
+        for entry in new.map_or_else(Vec::new, |new| entries(new.notes)) {
+            match removed_entries.iter().position(|e| *e == entry) {
+                Some(i) => {
+                    removed_entries.remove(i);
+                }
+                None => added_entries.push(entry),
+            }
+        }
+        Self { kind, version, old, new, added_entries, removed_entries }
+    }
+    /// Writes the changes to the title and entries of a modified release.
+    fn write_details(&self, out: &mut impl io::Write) -> io::Result<()> {
+        if let (true, Some(old), Some(new)) = (self.title_changed(), self.old, self.new) {
+            writeln!(out, "  title: {} -> {}", old.title, new.title)?;
+        }
+        for (marker, entries) in [('-', &self.removed_entries), ('+', &self.added_entries)] {
+            for entry in entries {
+                for (i, line) in entry.lines().enumerate() {
+                    if i == 0 {
+                        writeln!(out, "  {marker} {line}")?;
+                    } else if line.is_empty() {
+                        writeln!(out)?;
+                    } else {
+                        writeln!(out, "    {line}")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits notes into entries: top-level list items (with their nested items
+/// and continuation lines), headings, and paragraphs.
+fn entries(notes: &str) -> Vec<String> {
+    let mut entries: Vec<String> = vec![];
+    let mut prev_blank = true;
+    let mut prev_heading = false;
+    for line in notes.lines() {
+        if line.trim().is_empty() {
+            prev_blank = true;
+            continue;
+        }
+        let is_heading = line.starts_with('#');
+        let is_top_level = !line.starts_with([' ', '\t']);
+        let is_list_item = is_top_level && {
+            let marker_len = match line.as_bytes()[0] {
+                b'-' | b'*' | b'+' => 1,
+                _ => line.bytes().take_while(u8::is_ascii_digit).count() + 1,
+            };
+            line.get(marker_len - 1..marker_len).is_some_and(|m| "-*+.)".contains(m))
+                && line[marker_len..].starts_with([' ', '\t'])
+        };
+        match entries.last_mut() {
+            Some(entry)
+                if !is_heading
+                    && !prev_heading
+                    && !is_list_item
+                    && !(prev_blank && is_top_level) =>
+            {
+                entry.push('\n');
+                if prev_blank {
+                    entry.push('\n');
+                }
+                entry.push_str(line);
+            }
+            _ => entries.push(line.to_owned()),
+        }
+        prev_blank = false;
+        prev_heading = is_heading;
+    }
+    entries
+}
+
+/// Returns per-version changes from `old` to `new`.
+///
+/// Added and modified versions come first in the order of `new`, followed by
+/// removed versions in the order of `old`.
+fn changes<'a>(old: &'a Changelog<'a>, new: &'a Changelog<'a>) -> Vec<Change<'a>> {
+    let mut changes = vec![];
+    for (&version, release) in new {
+        match old.get(version) {
+            None => changes.push(Change::new(ChangeKind::Added, version, None, Some(release))),
+            // Other fields (e.g., package name) are derived from the title.
+            Some(old) if old.title != release.title || old.notes != release.notes => {
+                changes.push(Change::new(ChangeKind::Modified, version, Some(old), Some(release)));
+            }
+            Some(_) => {}
+        }
+    }
+    for (&version, release) in old {
+        if !new.contains_key(version) {
+            changes.push(Change::new(ChangeKind::Removed, version, Some(release), None));
+        }
+    }
+    changes
+}
+
+pub(crate) fn run(parser: lexopt::Parser) -> Result<()> {
+    let Some(args) = Args::parse(parser)? else { return Ok(()) };
+
+    let parser = crate::new_parser(args.version_format.as_deref(), args.prefix_format.as_deref())?;
+    let old_text = crate::read_changelog(&args.old)?;
+    let old = crate::parse(&parser, &old_text, &args.old)?;
+    let new_text = crate::read_changelog(&args.new)?;
+    let new = crate::parse(&parser, &new_text, &args.new)?;
+    let changes = changes(&old, &new);
+
+    let mut stdout = BufWriter::new(io::stdout().lock()); // Buffered because it is written many times.
+    if args.check {
+        let mut modified = vec![];
+        for change in &changes {
+            if change.kind == ChangeKind::Modified && change.released() {
+                writeln!(stdout, "modified {}", change.version)?;
+                change.write_details(&mut stdout)?;
+                modified.push(change.version);
+            }
+        }
+        stdout.flush()?;
+        if !modified.is_empty() {
+            bail!("release notes of released version(s) were modified: {}", modified.join(", "));
+        }
+        return Ok(());
+    }
+    if args.json {
+        let changes: Vec<_> = changes
+            .iter()
+            .map(|change| {
+                serde_json::json!({
+                    "version": change.version,
+                    "change": change.kind.as_str(),
+                    "released": change.released(),
+                    "title_changed": change.title_changed(),
+                    "notes_changed": change.notes_changed(),
+                    "added_entries": change.added_entries,
+                    "removed_entries": change.removed_entries,
+                })
+            })
+            .collect();
+        serde_json::to_writer(&mut stdout, &changes)?;
+    } else {
+        for change in &changes {
+            write!(stdout, "{} {}", change.kind.as_str(), change.version)?;
+            if change.kind == ChangeKind::Modified {
+                match (change.title_changed(), change.notes_changed()) {
+                    (true, true) => write!(stdout, " (title and notes)")?,
+                    (true, false) => write!(stdout, " (title)")?,
+                    (false, true) => write!(stdout, " (notes)")?,
+                    (false, false) => unreachable!(),
+                }
+                if change.released() {
+                    write!(stdout, " [released version]")?;
+                }
+                writeln!(stdout)?;
+                change.write_details(&mut stdout)?;
+            } else {
+                writeln!(stdout)?;
+            }
+        }
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Number of context lines around changes in unified diff.
+const CONTEXT: usize = 3;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Writes a unified diff of the given texts without file headers.
+pub(crate) fn unified_diff<W: io::Write>(out: &mut W, old: &str, new: &str) -> io::Result<()> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..].
+    let mut lcs = vec![vec![0_u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    // (op, old index, new index)
+    let mut ops = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push((Op::Equal, i, j));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push((Op::Delete, i, j));
+            i += 1;
+        } else {
+            ops.push((Op::Insert, i, j));
+            j += 1;
+        }
+    }
+
+    let mut pos = 0;
+    while let Some(first_change) = ops[pos..].iter().position(|&(op, ..)| op != Op::Equal) {
+        let start = (pos + first_change).saturating_sub(CONTEXT).max(pos);
+        // Extend the hunk while changes are separated by at most 2 * CONTEXT equal lines.
+        let mut end = pos + first_change;
+        let mut equal_run = 0;
+        for (k, &(op, ..)) in ops.iter().enumerate().skip(end) {
+            if op == Op::Equal {
+                equal_run += 1;
+                if equal_run > CONTEXT * 2 {
+                    break;
+                }
+            } else {
+                equal_run = 0;
+                end = k;
+            }
+        }
+        let end = (end + 1 + CONTEXT).min(ops.len());
+        let hunk = &ops[start..end];
+        let old_len = hunk.iter().filter(|&&(op, ..)| op != Op::Insert).count();
+        let new_len = hunk.iter().filter(|&&(op, ..)| op != Op::Delete).count();
+        let (_, old_start, new_start) = hunk[0];
+        // Line numbers are 1-based, except that an empty range starts at the
+        // line before it.
+        let old_start = if old_len == 0 { old_start } else { old_start + 1 };
+        let new_start = if new_len == 0 { new_start } else { new_start + 1 };
+        writeln!(out, "@@ -{old_start},{old_len} +{new_start},{new_len} @@")?;
+        for &(op, i, j) in hunk {
+            match op {
+                Op::Equal => writeln!(out, " {}", old[i])?,
+                Op::Delete => writeln!(out, "-{}", old[i])?,
+                Op::Insert => writeln!(out, "+{}", new[j])?,
+            }
+        }
+        pos = end;
+    }
+    Ok(())
+}
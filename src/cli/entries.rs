@@ -0,0 +1,230 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Rendering of changelog entries and insertion of them into existing changelogs.
+
+use std::{collections::BTreeMap, ops::Range};
+
+use parse_changelog::Parser;
+
+use crate::Result;
+
+/// Categories of changes defined by [Keep a Changelog][keepachangelog], in the
+/// canonical order.
+///
+/// [keepachangelog]: https://keepachangelog.com
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Category {
+    Added,
+    Changed,
+    Deprecated,
+    Removed,
+    Fixed,
+    Security,
+}
+
+impl Category {
+    pub(crate) const ALL: [Self; 6] =
+        [Self::Added, Self::Changed, Self::Deprecated, Self::Removed, Self::Fixed, Self::Security];
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Added => "Added",
+            Self::Changed => "Changed",
+            Self::Deprecated => "Deprecated",
+            Self::Removed => "Removed",
+            Self::Fixed => "Fixed",
+            Self::Security => "Security",
+        }
+    }
+
+    /// Parses the category name case-insensitively.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Changelog entries grouped by category.
+pub(crate) type Entries = BTreeMap<Category, Vec<String>>;
+
+/// Renders entries as a list of category subsections with the given heading
+/// level. The returned string has no leading or trailing newlines.
+pub(crate) fn render(level: usize, entries: &Entries) -> String {
+    render_sections(
+        level,
+        entries.iter().map(|(category, entries)| (category.name(), &entries[..])),
+    )
+}
+
+/// Renders subsections that have the given headings and lists.
+/// The returned string has no leading or trailing newlines.
+pub(crate) fn render_sections<'a>(
+    level: usize,
+    sections: impl IntoIterator<Item = (&'a str, &'a [String])>,
+) -> String {
+    let mut out = String::new();
+    for (heading, entries) in sections {
+        if entries.is_empty() {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push_str("\n\n");
+        }
+        out.push_str(&"#".repeat(level));
+        out.push(' ');
+        out.push_str(heading);
+        out.push_str("\n\n");
+        push_list(&mut out, entries);
+    }
+    out
+}
+
+fn push_list(out: &mut String, entries: &[String]) {
+    for (i, entry) in entries.iter().enumerate() {
+        if i != 0 {
+            out.push('\n');
+        }
+        out.push_str("- ");
+        for (j, line) in entry.trim().lines().enumerate() {
+            if j != 0 {
+                out.push('\n');
+                if !line.trim().is_empty() {
+                    out.push_str("  ");
+                }
+            }
+            out.push_str(line.trim_end());
+        }
+    }
+}
+
+/// Inserts entries into the 'Unreleased' section of the given changelog.
+///
+/// Entries are appended to the existing category subsections if present, and
+/// new subsections are added at the end of the section otherwise.
+pub(crate) fn insert_unreleased(parser: &Parser, text: &str, entries: &Entries) -> Result<String> {
+    let Some(unreleased) = parser.parse_iter(text).find(|r| r.version == "Unreleased") else {
+        bail!("no 'Unreleased' section was found in changelog")
+    };
+    let heading = unreleased.heading_range();
+    let level = (usize::from(unreleased.heading_level()) + 1).min(6);
+
+    let (notes_start, notes_end) = match crate::subslice_offset(text, unreleased.notes) {
+        Some(start) => (start, start + unreleased.notes.len()),
+        None => (heading.end, heading.end),
+    };
+    let mut notes = unreleased.notes.to_owned();
+    let mut remaining = Entries::new();
+    for (&category, entries) in entries {
+        if entries.is_empty() {
+            continue;
+        }
+        match category_end(&notes, level, category) {
+            Some((pos, has_entries)) => {
+                let mut list = String::from(if has_entries { "\n" } else { "\n\n" });
+                push_list(&mut list, entries);
+                notes.insert_str(pos, &list);
+            }
+            None => {
+                remaining.insert(category, entries.clone());
+            }
+        }
+    }
+    let new = render(level, &remaining);
+    if !new.is_empty() {
+        if !notes.is_empty() {
+            notes.push_str("\n\n");
+        }
+        notes.push_str(&new);
+    }
+
+    let mut out = String::with_capacity(text.len() + notes.len());
+    out.push_str(&text[..notes_start]);
+    if unreleased.notes.is_empty() && !notes.is_empty() {
+        out.push_str("\n\n");
+    }
+    out.push_str(&notes);
+    let rest = &text[notes_end..];
+    if !rest.trim_start().is_empty() && !rest.starts_with("\n\n") {
+        // Keep a blank line between the section and the next line.
+        out.push('\n');
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Returns the byte offset of the end of the subsection for the given category
+/// in `notes`, and whether the subsection has any content other than the
+/// heading.
+fn category_end(notes: &str, level: usize, category: Category) -> Option<(usize, bool)> {
+    // Use the same heading detection as release sections, so that headings in
+    // code blocks and comments are ignored.
+    let headings = parse_changelog::headings(notes);
+    let i = headings.iter().position(|heading| {
+        usize::from(heading.level) == level && Category::from_name(heading.title) == Some(category)
+    })?;
+    let start = headings[i].range.end;
+    let end = headings[i + 1..]
+        .iter()
+        .find(|heading| usize::from(heading.level) <= level)
+        .map_or(notes.len(), |heading| heading.range.start);
+    let end = start + notes[start..end].trim_end().len();
+    Some((end, end > start))
+}
+
+/// Inserts a new release section with the given title above the latest
+/// release in the given changelog. The body of the section is rendered by
+/// `render_body` with the heading level for subsections.
+///
+/// If there is no release other than 'Unreleased', the new section is added
+/// after the 'Unreleased' section.
+///
+/// Returns the new changelog and the range of the new section in it.
+pub(crate) fn insert_release(
+    parser: &Parser,
+    text: &str,
+    version: &str,
+    title: &str,
+    render_body: impl FnOnce(usize) -> String,
+) -> Result<(String, Range<usize>)> {
+    let mut latest = None;
+    let mut unreleased = None;
+    for release in parser.parse_iter(text) {
+        if release.version == version {
+            bail!("release note for '{version}' already exists in changelog");
+        }
+        if release.version == "Unreleased" {
+            unreleased.get_or_insert(release);
+        } else {
+            latest.get_or_insert(release);
+        }
+    }
+    let (pos, level) = if let Some(latest) = &latest {
+        (latest.heading_range().start, usize::from(latest.heading_level()))
+    } else if let Some(unreleased) = &unreleased {
+        let end = crate::subslice_offset(text, unreleased.notes)
+            .map_or(unreleased.heading_range().end, |start| start + unreleased.notes.len());
+        // Skip the newline after the section.
+        ((end + 1).min(text.len()), usize::from(unreleased.heading_level()))
+    } else {
+        bail!("no release was found in changelog")
+    };
+
+    let mut section = format!("{} {title}\n\n", "#".repeat(level));
+    let body = render_body((level + 1).min(6));
+    if !body.is_empty() {
+        section.push_str(&body);
+        section.push_str("\n\n");
+    }
+    if pos == text.len() {
+        section.pop();
+    }
+    let mut out = String::with_capacity(text.len() + section.len() + 1);
+    out.push_str(&text[..pos]);
+    if latest.is_none() {
+        out.push('\n');
+    }
+    let start = out.len();
+    out.push_str(&section);
+    let end = out.len();
+    out.push_str(&text[pos..]);
+    Ok((out, start..end))
+}
@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{
+    fs,
+    io::{self, Write as _},
+    path::PathBuf,
+};
+
+use lexopt::Arg::{Long, Short, Value};
+use parse_changelog::{FormatOptions, HeadingStyle};
+
+use crate::Result;
+
+static USAGE: &str = "parse-changelog fmt
+
+Normalize the formatting of the changelog.
+
+Level 1 and 2 headings are unified to one style, headings are surrounded by
+one blank line, bullet list markers are unified, link reference definitions
+are sorted (releases in the changelog order first), and Keep a Changelog
+categories in each release are sorted in the canonical order ('Added',
+'Changed', 'Deprecated', 'Removed', 'Fixed', 'Security'). Code blocks and HTML
+comments are left untouched.
+
+With --convert-headings, only level 1 and 2 headings (or only release headings
+with --releases-only) are converted to the specified style, and the rest of the
+changelog is left untouched.
+
+By default, the formatted changelog is printed to standard output. With
+--write, the changelog is rewritten in place. With --check, a diff is printed
+and the command exits with an error if the changelog is not formatted.
+
+USAGE:
+    parse-changelog fmt [OPTIONS] <PATH>
+
+ARGS:
+    <PATH>    Path to the changelog file (use '-' for standard input, or 'git:<REV>:<PATH>'
+              to read the file at the specified git revision)
+
+OPTIONS:
+        --heading-style <STYLE>       Style of level 1 and 2 headings [default: the style of the
+                                      first level 1 or 2 heading] [possible values: atx, setext]
+        --bullet <CHAR>               Marker of bullet list items [default: -] [possible values:
+                                      -, *, +]
+        --convert-headings <STYLE>    Only convert headings to the specified style [possible
+                                      values: atx, setext]
+        --releases-only               Only convert release headings (requires --convert-headings)
+        --write                       Rewrite the changelog in place
+        --check                       Exit with an error if the changelog is not formatted
+        --version-format <PATTERN>    Specify version format
+        --prefix-format <PATTERN>     Specify prefix format [aliases: prefix]
+    -h, --help                        Print help information
+";
+
+struct Args {
+    path: PathBuf,
+    heading_style: Option<HeadingStyle>,
+    bullet: Option<char>,
+    convert_headings: Option<HeadingStyle>,
+    releases_only: bool,
+    write: bool,
+    check: bool,
+    version_format: Option<String>,
+    prefix_format: Option<String>,
+}
+
+impl Args {
+    fn parse(mut parser: lexopt::Parser) -> Result<Option<Self>> {
+        let mut path: Option<PathBuf> = None;
+        let mut heading_style: Option<String> = None;
+        let mut bullet: Option<String> = None;
+        let mut convert_headings: Option<String> = None;
+        let mut releases_only = false;
+        let mut write = false;
+        let mut check = false;
+        let mut version_format = None;
+        let mut prefix_format = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("heading-style") => parse_opt!(parser, arg, heading_style),
+                Long("bullet") => parse_opt!(parser, arg, bullet),
+                Long("convert-headings") => parse_opt!(parser, arg, convert_headings),
+                Long("releases-only") => parse_flag!(arg, releases_only),
+                Long("write") => parse_flag!(arg, write),
+                Long("check") => parse_flag!(arg, check),
+                Long("version-format") => parse_opt!(parser, arg, version_format),
+                Long("prefix-format" | "prefix") => parse_opt!(parser, arg, prefix_format),
+                Short('h') | Long("help") => {
+                    print!("{USAGE}");
+                    return Ok(None);
+                }
+                Value(val) if path.is_none() => path = Some(val.into()),
+                _ => return Err(arg.unexpected().into()),
+            }
+        }
+
+        let Some(path) = path else { bail!("no changelog path specified") };
+        let heading_style = heading_style.as_deref().map(parse_heading_style).transpose()?;
+        let convert_headings = convert_headings.as_deref().map(parse_heading_style).transpose()?;
+        let bullet = match bullet {
+            None => None,
+            Some(bullet) => {
+                let mut chars = bullet.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(c),
+                    _ => bail!("invalid bullet '{bullet}' (expected '-', '*', or '+')"),
+                }
+            }
+        };
+        if convert_headings.is_some() {
+            if heading_style.is_some() {
+                crate::conflicts("--convert-headings", "--heading-style")?;
+            }
+            if bullet.is_some() {
+                crate::conflicts("--convert-headings", "--bullet")?;
+            }
+        } else if releases_only {
+            crate::requires("--releases-only", "--convert-headings")?;
+        }
+        if write {
+            if check {
+                crate::conflicts("--write", "--check")?;
+            }
+            if path.as_os_str() == "-" || crate::git_path(&path).is_some() {
+                bail!("--write requires a path to a file in the working tree");
+            }
+        }
+
+        Ok(Some(Self {
+            path,
+            heading_style,
+            bullet,
+            convert_headings,
+            releases_only,
+            write,
+            check,
+            version_format,
+            prefix_format,
+        }))
+    }
+}
+
+fn parse_heading_style(style: &str) -> Result<HeadingStyle> {
+    match style {
+        "atx" => Ok(HeadingStyle::Atx),
+        "setext" => Ok(HeadingStyle::Setext),
+        _ => bail!("invalid heading style '{style}' (expected 'atx' or 'setext')"),
+    }
+}
+
+pub(crate) fn run(parser: lexopt::Parser) -> Result<()> {
+    let Some(args) = Args::parse(parser)? else { return Ok(()) };
+
+    let parser = crate::new_parser(args.version_format.as_deref(), args.prefix_format.as_deref())?;
+    let mut options = FormatOptions::new();
+    if let Some(style) = args.heading_style {
+        options.heading_style(style);
+    }
+    if let Some(bullet) = args.bullet {
+        options.bullet(bullet)?;
+    }
+    let text = crate::read_changelog(&args.path)?;
+    let new = match args.convert_headings {
+        Some(style) => parser.convert_headings(&text, style, args.releases_only),
+        None => parser.format(&text, &options),
+    };
+
+    if args.check {
+        if new != text {
+            let path = crate::path_for_msg(&args.path).display();
+            let mut stdout = io::stdout().lock();
+            writeln!(stdout, "--- {path}")?;
+            writeln!(stdout, "+++ {path} (formatted)")?;
+            crate::diff::unified_diff(&mut stdout, &text, &new)?;
+            stdout.flush()?;
+            bail!("{path} is not formatted");
+        }
+        return Ok(());
+    }
+    if args.write {
+        if new != text {
+            fs::write(&args.path, new)
+                .map_err(|e| format!("failed to write to file `{}`: {e}", args.path.display()))?;
+        }
+        return Ok(());
+    }
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(new.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{
+    fs,
+    io::{self, Write as _},
+    path::PathBuf,
+};
+
+use lexopt::Arg::{Long, Short, Value};
+
+use crate::{
+    Result,
+    entries::{self, Category, Entries},
+    git,
+};
+
+static USAGE: &str = "parse-changelog generate
+
+Generate changelog entries from Conventional Commits in the git repository that
+contains the changelog.
+
+Reads commits since the most recent tag (or --since revision), and groups
+`feat` commits into 'Added', `fix` commits into 'Fixed', `perf` commits into
+'Changed', and commits with breaking changes (`!` after the type or a
+`BREAKING CHANGE` footer) into 'Changed'. Other commits are ignored.
+
+By default, the generated entries are printed to standard output. With --write,
+they are inserted into the 'Unreleased' section of the changelog.
+
+USAGE:
+    parse-changelog generate [OPTIONS] <PATH>
+
+ARGS:
+    <PATH>    Path to the changelog file
+
+OPTIONS:
+        --write                       Insert generated entries into the 'Unreleased' section of
+                                      the changelog
+        --since <REV>                 Read commits after the specified revision [default: the
+                                      most recent tag]
+        --version-format <PATTERN>    Specify version format
+        --prefix-format <PATTERN>     Specify prefix format [aliases: prefix]
+    -h, --help                        Print help information
+";
+
+struct Args {
+    path: PathBuf,
+    write: bool,
+    since: Option<String>,
+    version_format: Option<String>,
+    prefix_format: Option<String>,
+}
+
+impl Args {
+    fn parse(mut parser: lexopt::Parser) -> Result<Option<Self>> {
+        let mut path: Option<PathBuf> = None;
+        let mut write = false;
+        let mut since = None;
+        let mut version_format = None;
+        let mut prefix_format = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("write") => parse_flag!(arg, write),
+                Long("since") => parse_opt!(parser, arg, since),
+                Long("version-format") => parse_opt!(parser, arg, version_format),
+                Long("prefix-format" | "prefix") => parse_opt!(parser, arg, prefix_format),
+                Short('h') | Long("help") => {
+                    print!("{USAGE}");
+                    return Ok(None);
+                }
+                Value(val) if path.is_none() => path = Some(val.into()),
+                _ => return Err(arg.unexpected().into()),
+            }
+        }
+
+        let Some(path) = path else { bail!("no changelog path specified") };
+        if write && (path.as_os_str() == "-" || crate::git_path(&path).is_some()) {
+            bail!("--write requires a path to a file in the working tree");
+        }
+
+        Ok(Some(Self { path, write, since, version_format, prefix_format }))
+    }
+}
+
+/// A parsed [Conventional Commits](https://www.conventionalcommits.org) message.
+struct Commit<'a> {
+    kind: &'a str,
+    scope: Option<&'a str>,
+    description: &'a str,
+    breaking: bool,
+}
+
+impl<'a> Commit<'a> {
+    fn parse(message: &'a str) -> Option<Self> {
+        let mut lines = message.lines();
+        let header = lines.next()?;
+        let (prefix, description) = header.split_once(':')?;
+        let description = description.trim();
+        let (prefix, mut breaking) = match prefix.strip_suffix('!') {
+            Some(prefix) => (prefix, true),
+            None => (prefix, false),
+        };
+        let (kind, scope) = match prefix.split_once('(') {
+            Some((kind, scope)) => (kind, Some(scope.strip_suffix(')')?)),
+            None => (prefix, None),
+        };
+        if kind.is_empty()
+            || !kind.bytes().all(|b| b.is_ascii_alphanumeric())
+            || description.is_empty()
+        {
+            return None;
+        }
+        breaking |= lines.any(|line| {
+            line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:")
+        });
+        Some(Self { kind, scope, description, breaking })
+    }
+
+    fn category(&self) -> Option<Category> {
+        if self.breaking {
+            return Some(Category::Changed);
+        }
+        match self.kind.to_ascii_lowercase().as_str() {
+            "feat" => Some(Category::Added),
+            "fix" => Some(Category::Fixed),
+            "perf" => Some(Category::Changed),
+            _ => None,
+        }
+    }
+
+    fn entry(&self) -> String {
+        let mut entry = String::new();
+        if self.breaking {
+            entry.push_str("**Breaking:** ");
+        }
+        if let Some(scope) = self.scope {
+            entry.push_str(scope);
+            entry.push_str(": ");
+        }
+        entry.push_str(self.description);
+        entry
+    }
+}
+
+pub(crate) fn run(parser: lexopt::Parser) -> Result<()> {
+    let Some(args) = Args::parse(parser)? else { return Ok(()) };
+
+    // Read commits from the repository that contains the changelog.
+    let dir = if args.path.as_os_str() == "-" || crate::git_path(&args.path).is_some() {
+        None
+    } else {
+        args.path.parent().filter(|dir| !dir.as_os_str().is_empty())
+    };
+    let since = match args.since {
+        Some(since) => Some(since),
+        None => git::last_tag(dir)?,
+    };
+    let range = match &since {
+        Some(since) => format!("{since}..HEAD"),
+        None => "HEAD".to_owned(),
+    };
+    let mut entries = Entries::new();
+    for message in git::commit_messages(dir, &range)? {
+        let Some(commit) = Commit::parse(&message) else { continue };
+        if let Some(category) = commit.category() {
+            entries.entry(category).or_default().push(commit.entry());
+        }
+    }
+
+    if !args.write {
+        let text = entries::render(3, &entries);
+        if !text.is_empty() {
+            let mut stdout = io::stdout().lock();
+            writeln!(stdout, "{text}")?;
+            stdout.flush()?;
+        }
+        return Ok(());
+    }
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let parser = crate::new_parser(args.version_format.as_deref(), args.prefix_format.as_deref())?;
+    let text = crate::read_changelog(&args.path)?;
+    let new = entries::insert_unreleased(&parser, &text, &entries)?;
+    fs::write(&args.path, new)
+        .map_err(|e| format!("failed to write to file `{}`: {e}", args.path.display()))?;
+    Ok(())
+}
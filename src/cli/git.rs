@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Output, Stdio},
+};
+
+use crate::Result;
+
+fn output(dir: Option<&Path>, args: &[&str]) -> Result<Output> {
+    let mut cmd = Command::new("git");
+    if let Some(dir) = dir {
+        cmd.arg("-C").arg(dir);
+    }
+    cmd.args(args).stdin(Stdio::null());
+    Ok(cmd.output().map_err(|e| format!("failed to run `git`: {e}"))?)
+}
+
+/// Runs `git` with the given arguments in `dir` (or the current directory if
+/// `None`) and returns its standard output.
+fn run(dir: Option<&Path>, args: &[&str]) -> Result<String> {
+    let output = output(dir, args)?;
+    if !output.status.success() {
+        bail!(
+            "`git {}` failed ({}): {}",
+            args.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8(output.stdout).map_err(|e| format!("non-UTF-8 output from git: {e}"))?)
+}
+
+/// Returns all tags in the repository.
+pub(crate) fn tags(dir: Option<&Path>) -> Result<Vec<String>> {
+    let out = run(dir, &["tag", "--list"])?;
+    Ok(out.lines().filter(|l| !l.is_empty()).map(str::to_owned).collect())
+}
+
+/// Returns the contents of the file at `path` in the specified revision.
+///
+/// Relative paths are resolved from the current directory, not the root of the
+/// repository. Absolute paths are resolved from the root of the repository that
+/// contains the file.
+pub(crate) fn show(rev: &str, path: &str) -> Result<String> {
+    if Path::new(path).is_absolute() {
+        let path = Path::new(path);
+        let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+            bail!("`{}` is not a path to a file", path.display());
+        };
+        // Canonicalize both paths because the top-level directory reported by
+        // git has symbolic links resolved.
+        let canonicalize = |dir: &Path| {
+            fs::canonicalize(dir)
+                .map_err(|e| format!("failed to resolve path `{}`: {e}", dir.display()))
+        };
+        let dir = canonicalize(dir)?;
+        let top_level = run(Some(&dir), &["rev-parse", "--show-toplevel"])?;
+        let top_level = canonicalize(Path::new(top_level.trim_end()))?;
+        let path = dir.join(file_name);
+        let Some(relative) = path.strip_prefix(&top_level).ok().and_then(Path::to_str) else {
+            bail!("`{}` is not in the git repository `{}`", path.display(), top_level.display());
+        };
+        let relative = relative.replace('\\', "/");
+        return run(Some(&top_level), &["show", &format!("{rev}:{relative}")]);
+    }
+    let path = if path.starts_with("./") || path.starts_with("../") {
+        path.to_owned()
+    } else {
+        format!("./{path}")
+    };
+    run(None, &["show", &format!("{rev}:{path}")])
+}
+
+/// Returns the most recent tag reachable from `HEAD`, or `None` if there is no
+/// such tag.
+pub(crate) fn last_tag(dir: Option<&Path>) -> Result<Option<String>> {
+    let output = output(dir, &["describe", "--tags", "--abbrev=0"])?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let tag = String::from_utf8(output.stdout).map_err(|e| format!("non-UTF-8 tag name: {e}"))?;
+    Ok(Some(tag.trim_end().to_owned()))
+}
+
+/// Returns messages of non-merge commits in the given revision range, oldest first.
+pub(crate) fn commit_messages(dir: Option<&Path>, range: &str) -> Result<Vec<String>> {
+    let out = run(dir, &["log", "-z", "--no-merges", "--reverse", "--format=%B", range])?;
+    Ok(out.split('\0').filter(|m| !m.trim().is_empty()).map(str::to_owned).collect())
+}
@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{
+    io::{self, Write as _},
+    path::PathBuf,
+};
+
+use lexopt::Arg::{Long, Short, Value};
+
+use crate::{Result, git};
+
+static USAGE: &str = "parse-changelog tags
+
+Compare versions in changelog with tags in the local git repository.
+
+Reports tags that have no release note in the changelog and releases (other
+than 'Unreleased') that have no tag, and exits with an error if any are found.
+Tags that don't start with the tag prefix followed by a digit are ignored.
+
+USAGE:
+    parse-changelog tags [OPTIONS] <PATH>
+
+ARGS:
+    <PATH>    Path to the changelog file (use '-' for standard input, or 'git:<REV>:<PATH>' to
+              read the file at the specified git revision)
+
+OPTIONS:
+        --tag-prefix <PREFIX>         Prefix of tags [default: v]
+        --repo <DIR>                  Path to the git repository [default: current directory]
+        --version-format <PATTERN>    Specify version format
+        --prefix-format <PATTERN>     Specify prefix format [aliases: prefix]
+    -h, --help                        Print help information
+";
+
+struct Args {
+    path: PathBuf,
+    tag_prefix: Option<String>,
+    repo: Option<PathBuf>,
+    version_format: Option<String>,
+    prefix_format: Option<String>,
+}
+
+impl Args {
+    fn parse(mut parser: lexopt::Parser) -> Result<Option<Self>> {
+        let mut path = None;
+        let mut tag_prefix = None;
+        let mut repo = None;
+        let mut version_format = None;
+        let mut prefix_format = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("tag-prefix") => parse_opt!(parser, arg, tag_prefix),
+                Long("repo") => parse_opt!(parser, arg, repo),
+                Long("version-format") => parse_opt!(parser, arg, version_format),
+                Long("prefix-format" | "prefix") => parse_opt!(parser, arg, prefix_format),
+                Short('h') | Long("help") => {
+                    print!("{USAGE}");
+                    return Ok(None);
+                }
+                Value(val) if path.is_none() => path = Some(val.into()),
+                _ => return Err(arg.unexpected().into()),
+            }
+        }
+
+        let Some(path) = path else { bail!("no changelog path specified") };
+
+        Ok(Some(Self { path, tag_prefix, repo, version_format, prefix_format }))
+    }
+}
+
+pub(crate) fn run(parser: lexopt::Parser) -> Result<()> {
+    let Some(args) = Args::parse(parser)? else { return Ok(()) };
+
+    let parser = crate::new_parser(args.version_format.as_deref(), args.prefix_format.as_deref())?;
+    let text = crate::read_changelog(&args.path)?;
+    let changelog = crate::parse(&parser, &text, &args.path)?;
+    let tag_prefix = args.tag_prefix.as_deref().unwrap_or("v");
+    let tags = git::tags(args.repo.as_deref())?;
+
+    let mut stdout = io::stdout().lock();
+    let mut mismatches = 0;
+    let mut tagged = Vec::with_capacity(tags.len());
+    for tag in &tags {
+        let Some(version) = tag.strip_prefix(tag_prefix) else { continue };
+        if !version.starts_with(|c: char| c.is_ascii_digit()) {
+            continue;
+        }
+        tagged.push(version);
+        if !changelog.contains_key(version) {
+            mismatches += 1;
+            writeln!(stdout, "{tag}: tag has no release note in changelog")?;
+        }
+    }
+    for &version in changelog.keys() {
+        if version != "Unreleased" && !tagged.contains(&version) {
+            mismatches += 1;
+            writeln!(stdout, "{version}: release has no tag (expected '{tag_prefix}{version}')")?;
+        }
+    }
+    stdout.flush()?;
+
+    if mismatches != 0 {
+        bail!(
+            "found {mismatches} mismatch(es) between {} and git tags",
+            crate::path_for_msg(&args.path).display()
+        );
+    }
+    Ok(())
+}
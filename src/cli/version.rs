@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::cmp::Ordering;
+
+/// Compares two versions by [Semantic Versioning][semver] precedence.
+///
+/// This is lenient: numeric components are compared one by one regardless of
+/// their count (so "1.50" works), "Unreleased" is considered newer than any
+/// version, and versions that don't look like version numbers at all are
+/// compared as strings.
+///
+/// [semver]: https://semver.org
+pub(crate) fn cmp(a: &str, b: &str) -> Ordering {
+    match (a == "Unreleased", b == "Unreleased") {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        (false, false) => {}
+    }
+    match (Version::parse(a), Version::parse(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => a.cmp(b),
+    }
+}
+
+/// A kind of version bump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl std::str::FromStr for Bump {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "patch" => Ok(Self::Patch),
+            "minor" => Ok(Self::Minor),
+            "major" => Ok(Self::Major),
+            _ => Err(format!("unknown bump type '{s}' (expected major, minor, or patch)")),
+        }
+    }
+}
+
+/// Returns the version bumped by the given kind, or `None` if the given
+/// version is not a `MAJOR.MINOR.PATCH` version.
+///
+/// Like `npm version`, a pre-release version is bumped to its release version
+/// if that satisfies the given kind (e.g., minor bump of 1.1.0-rc.1 is 1.1.0).
+pub(crate) fn bump(version: &str, bump: Bump) -> Option<String> {
+    let version = Version::parse(version)?;
+    let &[major, minor, patch] = &version.numbers[..] else { return None };
+    let pre = version.pre.is_some();
+    let (major, minor, patch) = match bump {
+        Bump::Major if pre && minor == 0 && patch == 0 => (major, 0, 0),
+        Bump::Major => (major + 1, 0, 0),
+        Bump::Minor if pre && patch == 0 => (major, minor, 0),
+        Bump::Minor => (major, minor + 1, 0),
+        Bump::Patch if pre => (major, minor, patch),
+        Bump::Patch => (major, minor, patch + 1),
+    };
+    Some(format!("{major}.{minor}.{patch}"))
+}
+
+struct Version<'a> {
+    numbers: Vec<u64>,
+    pre: Option<&'a str>,
+}
+
+impl<'a> Version<'a> {
+    fn parse(s: &'a str) -> Option<Self> {
+        // Build metadata is ignored when determining precedence.
+        let s = s.split_once('+').map_or(s, |(s, _)| s);
+        let (numbers, pre) = match s.split_once('-') {
+            Some((numbers, pre)) => (numbers, Some(pre)),
+            None => (s, None),
+        };
+        let numbers = numbers.split('.').map(|n| n.parse().ok()).collect::<Option<Vec<u64>>>()?;
+        Some(Self { numbers, pre })
+    }
+}
+
+// Equality is defined by the precedence, so that it is consistent with `Ord`
+// (e.g., `1.0` and `1.0.0` are equal).
+impl PartialEq for Version<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version<'_> {}
+
+impl PartialOrd for Version<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.numbers.len().max(other.numbers.len());
+        for i in 0..len {
+            let a = self.numbers.get(i).copied().unwrap_or(0);
+            let b = other.numbers.get(i).copied().unwrap_or(0);
+            match a.cmp(&b) {
+                Ordering::Equal => {}
+                ord => return ord,
+            }
+        }
+        match (self.pre, other.pre) {
+            (None, None) => Ordering::Equal,
+            // A pre-release version has lower precedence than a normal version.
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => cmp_pre(a, b),
+        }
+    }
+}
+
+fn cmp_pre(a: &str, b: &str) -> Ordering {
+    let mut a = a.split('.');
+    let mut b = b.split('.');
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => {
+                let ord = match (a.parse::<u64>(), b.parse::<u64>()) {
+                    (Ok(a), Ok(b)) => a.cmp(&b),
+                    // Numeric identifiers have lower precedence than alphanumeric ones.
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => a.cmp(b),
+                };
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+        }
+    }
+}
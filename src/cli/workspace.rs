@@ -0,0 +1,279 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Support for Cargo workspaces.
+//
+// Manifests are parsed by ourselves instead of using `cargo metadata` to
+// avoid depending on cargo and network access.
+
+use std::{
+    fs,
+    io::{self, BufWriter, Write as _},
+    path::{Path, PathBuf},
+};
+
+use parse_changelog::Parser;
+use toml::{Table, Value};
+
+use crate::Result;
+
+/// A package in a Cargo workspace.
+pub(crate) struct Package {
+    pub(crate) name: String,
+    pub(crate) version: String,
+    /// Path to the directory that contains the manifest of this package.
+    pub(crate) dir: PathBuf,
+}
+
+fn read_manifest(path: &Path) -> Result<Table> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read from file `{}`: {e}", path.display()))?;
+    Ok(text.parse().map_err(|e| format!("failed to parse `{}`: {e}", path.display()))?)
+}
+
+fn str_array<'a>(table: &'a Table, key: &str, path: &Path) -> Result<Vec<&'a str>> {
+    match table.get(key) {
+        None => Ok(vec![]),
+        Some(Value::Array(array)) => array
+            .iter()
+            .map(|v| {
+                v.as_str().ok_or_else(|| {
+                    format!("`{key}` must be an array of strings in `{}`", path.display()).into()
+                })
+            })
+            .collect(),
+        Some(_) => bail!("`{key}` must be an array of strings in `{}`", path.display()),
+    }
+}
+
+/// Returns packages in the workspace, in the order of the root package (if
+/// any) followed by `workspace.members`. Packages without `package.version`
+/// (allowed since Cargo 1.75) are ignored because they are never published.
+pub(crate) fn packages(manifest_path: &Path) -> Result<Vec<Package>> {
+    let manifest = read_manifest(manifest_path)?;
+    let root = manifest_path.parent().unwrap_or_else(|| Path::new(""));
+    let workspace = manifest.get("workspace").and_then(Value::as_table);
+
+    let mut dirs = vec![];
+    if manifest.contains_key("package") {
+        dirs.push(root.to_owned());
+    }
+    if let Some(workspace) = workspace {
+        let exclude = str_array(workspace, "exclude", manifest_path)?;
+        for member in str_array(workspace, "members", manifest_path)? {
+            for dir in glob(root, member, "Cargo.toml") {
+                if !dirs.contains(&dir) && !exclude.iter().any(|e| root.join(e) == dir) {
+                    dirs.push(dir);
+                }
+            }
+        }
+    }
+
+    let workspace_version = workspace.and_then(|w| w.get("package")?.get("version")?.as_str());
+    let mut packages = Vec::with_capacity(dirs.len());
+    for dir in dirs {
+        let path = dir.join("Cargo.toml");
+        let member;
+        let manifest = if dir == root {
+            &manifest
+        } else {
+            member = read_manifest(&path)?;
+            &member
+        };
+        let (name, version) = name_and_version(manifest, &path, |name| match workspace_version {
+            Some(version) => Ok(version.to_owned()),
+            None => bail!(
+                "`package.version` of '{name}' is inherited from the workspace, but \
+                 no `workspace.package.version` in `{}`",
+                manifest_path.display()
+            ),
+        })?;
+        let Some(version) = version else { continue };
+        packages.push(Package { name: name.to_owned(), version, dir });
+    }
+    Ok(packages)
+}
+
+/// Returns `package.version` in the given manifest.
+///
+/// If the version is inherited from the workspace, this searches the parent
+/// directories for the workspace root.
+pub(crate) fn package_version(manifest_path: &Path) -> Result<String> {
+    let manifest = read_manifest(manifest_path)?;
+    let (_, version) = name_and_version(&manifest, manifest_path, |name| {
+        let dir = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+        let dir = fs::canonicalize(if dir.as_os_str().is_empty() { Path::new(".") } else { dir })?;
+        for dir in dir.ancestors() {
+            let path = dir.join("Cargo.toml");
+            if !path.is_file() {
+                continue;
+            }
+            let Some(workspace) = read_manifest(&path)?.remove("workspace") else { continue };
+            match workspace.get("package").and_then(|p| p.get("version")?.as_str()) {
+                Some(version) => return Ok(version.to_owned()),
+                None => bail!(
+                    "`package.version` of '{name}' is inherited from the workspace, but \
+                     no `workspace.package.version` in `{}`",
+                    path.display()
+                ),
+            }
+        }
+        bail!(
+            "`package.version` of '{name}' is inherited from the workspace, but \
+             no workspace root was found for `{}`",
+            manifest_path.display()
+        )
+    })?;
+    match version {
+        Some(version) => Ok(version),
+        None => bail!("no `package.version` in `{}`", manifest_path.display()),
+    }
+}
+
+/// Returns `package.name` and `package.version` (or `None` if not specified)
+/// in the given manifest. `workspace_version` is called with the package name
+/// if the version is inherited from the workspace.
+fn name_and_version<'a>(
+    manifest: &'a Table,
+    path: &Path,
+    workspace_version: impl FnOnce(&str) -> Result<String>,
+) -> Result<(&'a str, Option<String>)> {
+    let Some(package) = manifest.get("package").and_then(Value::as_table) else {
+        bail!("no `package` table in `{}`", path.display())
+    };
+    let Some(name) = package.get("name").and_then(Value::as_str) else {
+        bail!("no `package.name` in `{}`", path.display())
+    };
+    let version = match package.get("version") {
+        None => None,
+        Some(Value::String(version)) => Some(version.clone()),
+        Some(Value::Table(t)) if t.get("workspace").and_then(Value::as_bool) == Some(true) => {
+            Some(workspace_version(name)?)
+        }
+        Some(_) => bail!("invalid `package.version` in `{}`", path.display()),
+    };
+    Ok((name, version))
+}
+
+/// Expands a workspace member pattern. Only `*` in path components is
+/// supported as a wildcard, and directories matched by a wildcard are ignored
+/// if they don't contain the given manifest file (e.g., Cargo.toml).
+pub(crate) fn glob(root: &Path, pattern: &str, manifest: &str) -> Vec<PathBuf> {
+    if !pattern.contains('*') {
+        return vec![root.join(pattern)];
+    }
+    let mut dirs = vec![root.to_owned()];
+    for component in pattern.split('/') {
+        if !component.contains('*') {
+            for dir in &mut dirs {
+                dir.push(component);
+            }
+            continue;
+        }
+        let mut matched = vec![];
+        for dir in &dirs {
+            let Ok(read_dir) = fs::read_dir(dir) else { continue };
+            let mut names: Vec<_> = read_dir
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .filter(|name| wildcard_match(component, name))
+                .collect();
+            names.sort();
+            matched.extend(names.into_iter().map(|name| dir.join(name)));
+        }
+        dirs = matched;
+    }
+    dirs.retain(|dir| dir.join(manifest).is_file());
+    dirs
+}
+
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => name.strip_prefix(prefix).is_some_and(|name| {
+            (0..=name.len()).any(|i| name.is_char_boundary(i) && wildcard_match(rest, &name[i..]))
+        }),
+    }
+}
+
+/// Outputs release notes for the current version of each package in the
+/// workspace. Packages without CHANGELOG.md are ignored.
+///
+/// Failures for individual packages (e.g., no release note for the current
+/// version) don't stop the output for other packages, and are reported
+/// together at the end.
+pub(crate) fn run(parser: &Parser, manifest_path: &Path, json: bool) -> Result<()> {
+    let mut changelogs = vec![];
+    let mut failures = vec![];
+    for package in packages(manifest_path)? {
+        let path = package.dir.join("CHANGELOG.md");
+        if path.is_file() {
+            match crate::read_changelog(&path) {
+                Ok(text) => changelogs.push((package, path, text)),
+                Err(e) => failures.push(e.to_string()),
+            }
+        }
+    }
+    if changelogs.is_empty() && failures.is_empty() {
+        bail!("no changelog was found in workspace `{}`", manifest_path.display());
+    }
+
+    let mut releases = Vec::with_capacity(changelogs.len());
+    for (package, path, text) in &changelogs {
+        let changelog = match crate::parse(parser, text, path) {
+            Ok(changelog) => changelog,
+            Err(e) => {
+                failures.push(e.to_string());
+                continue;
+            }
+        };
+        let Some(release) = changelog.get(&*package.version) else {
+            failures.push(format!(
+                "not found release note for '{}' of '{}' in {}",
+                package.version,
+                package.name,
+                path.display()
+            ));
+            continue;
+        };
+        releases.push((package, path, release.clone()));
+    }
+
+    let mut stdout = BufWriter::new(io::stdout().lock()); // Buffered because it is written many times.
+    if json {
+        let releases: Vec<_> = releases
+            .iter()
+            .map(|(package, path, release)| {
+                serde_json::json!({
+                    "name": package.name,
+                    "version": package.version,
+                    "path": path,
+                    "title": release.title,
+                    "notes": release.notes,
+                    "anchor": release.anchor(),
+                    "references": crate::references_json(release),
+                })
+            })
+            .collect();
+        serde_json::to_writer(&mut stdout, &releases)?;
+    } else {
+        for (i, (package, _, release)) in releases.iter().enumerate() {
+            if i != 0 {
+                writeln!(stdout)?;
+            }
+            writeln!(stdout, "## {} {}", package.name, package.version)?;
+            if !release.notes.is_empty() {
+                writeln!(stdout)?;
+                writeln!(stdout, "{}", release.notes)?;
+            }
+        }
+    }
+    stdout.flush()?;
+    if !failures.is_empty() {
+        bail!(
+            "failed to get release notes for {} package(s) in workspace `{}`:\n  {}",
+            failures.len(),
+            manifest_path.display(),
+            failures.join("\n  ")
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,429 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Formatting of changelogs.
+
+use alloc::{borrow::ToOwned as _, format, string::String, vec::Vec};
+use core::ops::Range;
+
+use crate::{
+    HeadingStyle, Lines, Parser, default_prefix_format, default_version_format,
+    error::{Error, Result},
+    extract_version_from_title, for_each_heading, handle_non_heading_line, heading,
+    links::link_definition,
+    split_link, trim_start,
+};
+
+/// [Keep a Changelog] categories in the canonical order.
+///
+/// [Keep a Changelog]: https://keepachangelog.com
+const CATEGORIES: [&str; 6] = ["Added", "Changed", "Deprecated", "Removed", "Fixed", "Security"];
+
+/// Options for [`Parser::format`](crate::Parser::format).
+#[derive(Debug, Clone, Default)]
+pub struct FormatOptions {
+    heading_style: Option<HeadingStyle>,
+    bullet: Option<u8>,
+}
+
+impl FormatOptions {
+    /// Creates a new `FormatOptions` with the default options.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the style of level 1 and 2 headings.
+    ///
+    /// Headings of level 3 or higher are always Atx-style headings, because
+    /// Setext-style headings only support levels 1 and 2.
+    ///
+    /// By default, the style of the first level 1 or 2 heading in the
+    /// changelog is used.
+    pub fn heading_style(&mut self, style: HeadingStyle) -> &mut Self {
+        self.heading_style = Some(style);
+        self
+    }
+
+    /// Sets the marker of bullet list items.
+    ///
+    /// The default is `-`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the marker is not `-`, `*`, or `+`.
+    pub fn bullet(&mut self, bullet: char) -> Result<&mut Self> {
+        if !matches!(bullet, '-' | '*' | '+') {
+            return Err(Error::format(format!(
+                "invalid bullet '{bullet}' (expected '-', '*', or '+')"
+            )));
+        }
+        self.bullet = Some(bullet as u8);
+        Ok(self)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Item<'a> {
+    Heading {
+        text: &'a str,
+        level: u8,
+        style: HeadingStyle,
+    },
+    Blank,
+    /// A line that is not in code blocks or comments.
+    Line(&'a str),
+    /// A line that is output as is. (a line in code blocks or comments)
+    Verbatim(&'a str),
+}
+
+pub(crate) fn format(parser: &Parser, text: &str, options: &FormatOptions) -> String {
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut items = items(text);
+
+    let prefix_format = parser.prefix_format.as_ref().unwrap_or_else(|| default_prefix_format());
+    let is_release = release_matcher(parser);
+    let release_level = items.iter().find_map(|item| match *item {
+        Item::Heading { text, level, .. } if is_release(text) => Some(level),
+        _ => None,
+    });
+    // Labels of link reference definitions of releases. (e.g., `1.0.0` in `## [1.0.0] - 2020-01-01`)
+    let mut release_labels = Vec::new();
+    if let Some(release_level) = release_level {
+        for item in &items {
+            if let Item::Heading { text, level, .. } = *item {
+                if level == release_level && is_release(text) {
+                    let label = match split_link(text) {
+                        Some((label, "", _)) => label,
+                        _ => extract_version_from_title(text, prefix_format).0,
+                    };
+                    release_labels.push(label);
+                }
+            }
+        }
+        sort_categories(&mut items, release_level, &is_release);
+    }
+    sort_definitions(&mut items, &release_labels);
+
+    let style = options.heading_style.unwrap_or_else(|| {
+        items
+            .iter()
+            .find_map(|item| match *item {
+                Item::Heading { level: 1 | 2, style, .. } => Some(style),
+                _ => None,
+            })
+            .unwrap_or(HeadingStyle::Atx)
+    });
+    render(&items, style, options.bullet.unwrap_or(b'-'), newline)
+}
+
+/// Returns a function that returns `true` if the given heading text is a title
+/// of a release.
+fn release_matcher(parser: &Parser) -> impl Fn(&str) -> bool + '_ {
+    let version_format = parser.version_format.as_ref().unwrap_or_else(|| default_version_format());
+    let prefix_format = parser.prefix_format.as_ref().unwrap_or_else(|| default_prefix_format());
+    |text: &str| version_format.is_match(extract_version_from_title(text, prefix_format).0)
+}
+
+/// Removes the closing sequence of an Atx-style heading. (e.g., `## Title ##`)
+pub(crate) fn strip_closing_sequence(text: &str) -> &str {
+    let t = text.trim_end_matches('#');
+    if t.is_empty() || t.ends_with([' ', '\t']) { t.trim_end() } else { text }
+}
+
+pub(crate) fn convert_headings(
+    parser: &Parser,
+    text: &str,
+    style: HeadingStyle,
+    releases_only: bool,
+) -> String {
+    let is_release = release_matcher(parser);
+    let mut release_level = None;
+    if releases_only {
+        for_each_heading(text, |heading, _| {
+            if release_level.is_none() && is_release(heading.text) {
+                release_level = Some(heading.level);
+            }
+        });
+        if release_level.is_none() {
+            return text.to_owned();
+        }
+    }
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+
+    let mut buf = String::with_capacity(text.len() + 16);
+    let mut last = 0;
+    let mut prev_heading_end = None;
+    for_each_heading(text, |heading, range| {
+        let after_heading =
+            prev_heading_end.replace(range.end).is_some_and(|end| end + 1 == range.start);
+        // Setext-style headings only support levels 1 and 2.
+        if heading.style == style
+            || heading.level > 2
+            || releases_only && (Some(heading.level) != release_level || !is_release(heading.text))
+        {
+            return;
+        }
+        // Keep the carriage return of the last line.
+        let end = if text[..range.end].ends_with('\r') { range.end - 1 } else { range.end };
+        match style {
+            HeadingStyle::Atx => {
+                buf.push_str(&text[last..range.start]);
+                for _ in 0..heading.level {
+                    buf.push('#');
+                }
+                buf.push(' ');
+                buf.push_str(heading.text);
+            }
+            HeadingStyle::Setext => {
+                let title = strip_closing_sequence(heading.text);
+                if title.is_empty() {
+                    return;
+                }
+                buf.push_str(&text[last..range.start]);
+                // A Setext-style heading just after a paragraph line is
+                // treated as a part of the paragraph.
+                let prev_line = text[..range.start].strip_suffix('\n').unwrap_or_default();
+                let prev_line = prev_line.rsplit('\n').next().unwrap_or_default();
+                if !after_heading && !prev_line.trim().is_empty() {
+                    buf.push_str(newline);
+                }
+                buf.push_str(title);
+                buf.push_str(newline);
+                let underline = if heading.level == 1 { '=' } else { '-' };
+                for _ in 0..title.chars().count().max(3) {
+                    buf.push(underline);
+                }
+            }
+        }
+        last = end;
+    });
+    buf.push_str(&text[last..]);
+    buf
+}
+
+/// Splits the text into items.
+fn items(text: &str) -> Vec<Item<'_>> {
+    let mut items = Vec::new();
+    let mut on_code_block: Option<&[u8]> = None;
+    let mut on_comment = false;
+    let mut is_inline_comment = false;
+    let mut lines = Lines::new(text);
+    while let Some((line, ..)) = lines.peek() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let trimmed = trim_start(line);
+        let verbatim = on_code_block.is_some() || on_comment && !is_inline_comment;
+        let heading = if verbatim { None } else { heading(trimmed, &mut lines) };
+        lines.next();
+        match heading {
+            // An underline after a blank line is a thematic break.
+            Some(heading) if heading.style == HeadingStyle::Atx || !heading.text.is_empty() => {
+                on_comment = false;
+                let mut text = heading.text;
+                if heading.style == HeadingStyle::Setext {
+                    // Skip an underline after a Setext-style heading.
+                    lines.next();
+                } else {
+                    text = strip_closing_sequence(text);
+                }
+                items.push(Item::Heading { text, level: heading.level, style: heading.style });
+            }
+            _ => {
+                handle_non_heading_line(
+                    &mut on_code_block,
+                    &mut on_comment,
+                    &mut is_inline_comment,
+                    trimmed.as_bytes(),
+                );
+                items.push(
+                    if verbatim || on_code_block.is_some() || on_comment || line.contains("<!--") {
+                        Item::Verbatim(line)
+                    } else if line.trim().is_empty() {
+                        Item::Blank
+                    } else {
+                        Item::Line(line)
+                    },
+                );
+            }
+        }
+    }
+    // Trailing blank lines are removed even if the text ends in an unclosed
+    // code block or comment, like trailing blank lines outside of them.
+    while matches!(items.last(), Some(Item::Verbatim(line)) if line.trim().is_empty()) {
+        items.pop();
+    }
+    items
+}
+
+/// Sorts subsections of each release whose titles are Keep a Changelog
+/// categories in the canonical order.
+fn sort_categories(
+    items: &mut Vec<Item<'_>>,
+    release_level: u8,
+    is_release: &dyn Fn(&str) -> bool,
+) {
+    let sub_level = release_level + 1;
+    let mut i = 0;
+    while i < items.len() {
+        let start = i + 1;
+        let end = items[start..]
+            .iter()
+            .position(|item| matches!(*item, Item::Heading { level, .. } if level <= release_level))
+            .map_or(items.len(), |pos| start + pos);
+        let Item::Heading { text, level, .. } = items[i] else {
+            i = end;
+            continue;
+        };
+        i = end;
+        if level != release_level || !is_release(text) {
+            continue;
+        }
+
+        // Subsections and their category indices.
+        let mut subsections: Vec<(Range<usize>, Option<usize>)> = Vec::new();
+        for (j, item) in items.iter().enumerate().take(end).skip(start) {
+            if let Item::Heading { text, level, .. } = *item {
+                if level == sub_level {
+                    if let Some((last, _)) = subsections.last_mut() {
+                        last.end = j;
+                    }
+                    let category = text.trim_end_matches(':');
+                    let category = CATEGORIES.iter().position(|c| c.eq_ignore_ascii_case(category));
+                    subsections.push((j..end, category));
+                }
+            }
+        }
+        // Link reference definitions and thematic breaks at the end of the
+        // release are not part of the last subsection.
+        if let Some((last, _)) = subsections.last_mut() {
+            while last.end > last.start + 1 {
+                match items[last.end - 1] {
+                    Item::Blank => {}
+                    Item::Line(line)
+                        if link_definition(trim_start(line)).is_some()
+                            || is_thematic_break(line) => {}
+                    _ => break,
+                }
+                last.end -= 1;
+            }
+        }
+        let mut categories: Vec<_> = subsections.iter().filter(|(_, c)| c.is_some()).collect();
+        if categories.is_sorted_by_key(|(_, c)| *c) {
+            continue;
+        }
+        categories.sort_by_key(|(_, c)| *c);
+        let (Some((first, _)), Some((last, _))) = (subsections.first(), subsections.last()) else {
+            continue;
+        };
+        let mut new = Vec::with_capacity(last.end - first.start);
+        let mut categories = categories.into_iter();
+        for (range, category) in &subsections {
+            let range = match category {
+                Some(_) => &categories.next().unwrap().0,
+                None => range,
+            };
+            new.extend_from_slice(&items[range.clone()]);
+        }
+        items.splice(first.start..last.end, new);
+    }
+}
+
+/// Sorts each group of consecutive link reference definitions.
+fn sort_definitions(items: &mut [Item<'_>], release_labels: &[&str]) {
+    fn label<'a>(item: &Item<'a>) -> Option<&'a str> {
+        match *item {
+            Item::Line(line) => {
+                link_definition(trim_start(line)).map(|definition| definition.label)
+            }
+            _ => None,
+        }
+    }
+    let key = |item: &Item<'_>| {
+        let label = label(item).unwrap_or_default();
+        match release_labels.iter().position(|l| l.eq_ignore_ascii_case(label)) {
+            Some(pos) => (0, pos, String::new()),
+            None => (1, 0, label.to_lowercase()),
+        }
+    };
+    let mut i = 0;
+    while i < items.len() {
+        let len = items[i..].iter().take_while(|item| label(item).is_some()).count();
+        if len > 1 {
+            items[i..i + len].sort_by_cached_key(key);
+        }
+        i += len.max(1);
+    }
+}
+
+fn render(items: &[Item<'_>], style: HeadingStyle, bullet: u8, newline: &str) -> String {
+    let mut out = String::new();
+    let mut blanks = 0;
+    let mut after_heading = false;
+    for item in items {
+        match *item {
+            Item::Blank => blanks += 1,
+            Item::Heading { text, level, .. } => {
+                if !out.is_empty() {
+                    out.push_str(newline);
+                }
+                if style == HeadingStyle::Setext && level <= 2 && !text.is_empty() {
+                    out.push_str(text);
+                    out.push_str(newline);
+                    let underline = if level == 1 { '=' } else { '-' };
+                    for _ in 0..text.chars().count().max(3) {
+                        out.push(underline);
+                    }
+                } else {
+                    for _ in 0..level {
+                        out.push('#');
+                    }
+                    if !text.is_empty() {
+                        out.push(' ');
+                        out.push_str(text);
+                    }
+                }
+                out.push_str(newline);
+                blanks = 0;
+                after_heading = true;
+            }
+            Item::Line(line) | Item::Verbatim(line) => {
+                if after_heading {
+                    blanks = 1;
+                    after_heading = false;
+                }
+                for _ in 0..blanks {
+                    out.push_str(newline);
+                }
+                blanks = 0;
+                match *item {
+                    Item::Line(line) => push_line(line, bullet, &mut out),
+                    _ => out.push_str(line),
+                }
+                out.push_str(newline);
+            }
+        }
+    }
+    out
+}
+
+/// Pushes a line with the marker of the bullet list item replaced.
+fn push_line(line: &str, bullet: u8, out: &mut String) {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let is_bullet = matches!(trimmed.as_bytes(), [b'-' | b'*' | b'+', b' ' | b'\t', ..])
+        && !is_thematic_break(trimmed);
+    if is_bullet {
+        out.push_str(&line[..indent]);
+        out.push(bullet as char);
+        out.push_str(&trimmed[1..]);
+    } else {
+        out.push_str(line);
+    }
+}
+
+/// Returns `true` if the line is a thematic break. (e.g., `* * *`)
+fn is_thematic_break(line: &str) -> bool {
+    let line = line.trim();
+    let Some(&marker @ (b'-' | b'*' | b'_')) = line.as_bytes().first() else { return false };
+    line.bytes().filter(|&b| b == marker).count() >= 3
+        && line.bytes().all(|b| b == marker || b == b' ' || b == b'\t')
+}
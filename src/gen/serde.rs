@@ -10,10 +10,11 @@ impl Serialize for crate::Release<'_> {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("Release", 3)?;
+        let mut state = serializer.serialize_struct("Release", 4)?;
         state.serialize_field("version", &self.version)?;
         state.serialize_field("title", &self.title)?;
         state.serialize_field("notes", &self.notes)?;
+        state.serialize_field("package", &self.package)?;
         state.end()
     }
 }
@@ -16,21 +16,76 @@ fn assert_unpin<T: ?Sized + Unpin>() {}
 fn assert_unwind_safe<T: ?Sized + std::panic::UnwindSafe>() {}
 fn assert_ref_unwind_safe<T: ?Sized + std::panic::RefUnwindSafe>() {}
 const _: fn() = || {
+    assert_send::<crate::autolink::AutoLinker>();
+    assert_sync::<crate::autolink::AutoLinker>();
+    assert_unpin::<crate::autolink::AutoLinker>();
+    assert_unwind_safe::<crate::autolink::AutoLinker>();
+    assert_ref_unwind_safe::<crate::autolink::AutoLinker>();
     assert_send::<crate::error::Error>();
     assert_sync::<crate::error::Error>();
     assert_unpin::<crate::error::Error>();
     assert_unwind_safe::<crate::error::Error>();
     assert_ref_unwind_safe::<crate::error::Error>();
+    assert_send::<crate::fmt::FormatOptions>();
+    assert_sync::<crate::fmt::FormatOptions>();
+    assert_unpin::<crate::fmt::FormatOptions>();
+    assert_unwind_safe::<crate::fmt::FormatOptions>();
+    assert_ref_unwind_safe::<crate::fmt::FormatOptions>();
+    #[cfg(feature = "html")]
+    assert_send::<crate::html::HtmlOptions>();
+    #[cfg(feature = "html")]
+    assert_sync::<crate::html::HtmlOptions>();
+    #[cfg(feature = "html")]
+    assert_unpin::<crate::html::HtmlOptions>();
+    #[cfg(feature = "html")]
+    assert_unwind_safe::<crate::html::HtmlOptions>();
+    #[cfg(feature = "html")]
+    assert_ref_unwind_safe::<crate::html::HtmlOptions>();
+    assert_send::<crate::links::LinkDefinition<'_>>();
+    assert_sync::<crate::links::LinkDefinition<'_>>();
+    assert_unpin::<crate::links::LinkDefinition<'_>>();
+    assert_unwind_safe::<crate::links::LinkDefinition<'_>>();
+    assert_ref_unwind_safe::<crate::links::LinkDefinition<'_>>();
+    assert_send::<crate::links::LinkResolver>();
+    assert_sync::<crate::links::LinkResolver>();
+    assert_unpin::<crate::links::LinkResolver>();
+    assert_unwind_safe::<crate::links::LinkResolver>();
+    assert_ref_unwind_safe::<crate::links::LinkResolver>();
+    assert_send::<crate::plain::PlainTextOptions>();
+    assert_sync::<crate::plain::PlainTextOptions>();
+    assert_unpin::<crate::plain::PlainTextOptions>();
+    assert_unwind_safe::<crate::plain::PlainTextOptions>();
+    assert_ref_unwind_safe::<crate::plain::PlainTextOptions>();
+    assert_send::<crate::references::References<'_>>();
+    assert_sync::<crate::references::References<'_>>();
+    assert_unpin::<crate::references::References<'_>>();
+    assert_unwind_safe::<crate::references::References<'_>>();
+    assert_ref_unwind_safe::<crate::references::References<'_>>();
+    assert_send::<crate::truncate::TruncateOptions>();
+    assert_sync::<crate::truncate::TruncateOptions>();
+    assert_unpin::<crate::truncate::TruncateOptions>();
+    assert_unwind_safe::<crate::truncate::TruncateOptions>();
+    assert_ref_unwind_safe::<crate::truncate::TruncateOptions>();
     assert_send::<crate::Changelog<'_>>();
     assert_sync::<crate::Changelog<'_>>();
     assert_unpin::<crate::Changelog<'_>>();
     assert_unwind_safe::<crate::Changelog<'_>>();
     assert_ref_unwind_safe::<crate::Changelog<'_>>();
+    assert_send::<crate::PackageChangelog<'_>>();
+    assert_sync::<crate::PackageChangelog<'_>>();
+    assert_unpin::<crate::PackageChangelog<'_>>();
+    assert_unwind_safe::<crate::PackageChangelog<'_>>();
+    assert_ref_unwind_safe::<crate::PackageChangelog<'_>>();
     assert_send::<crate::Release<'_>>();
     assert_sync::<crate::Release<'_>>();
     assert_unpin::<crate::Release<'_>>();
     assert_unwind_safe::<crate::Release<'_>>();
     assert_ref_unwind_safe::<crate::Release<'_>>();
+    assert_send::<crate::HeadingStyle>();
+    assert_sync::<crate::HeadingStyle>();
+    assert_unpin::<crate::HeadingStyle>();
+    assert_unwind_safe::<crate::HeadingStyle>();
+    assert_ref_unwind_safe::<crate::HeadingStyle>();
     assert_send::<crate::Parser>();
     assert_sync::<crate::Parser>();
     assert_unpin::<crate::Parser>();
@@ -25,7 +25,16 @@ fn write_size<T>(out: &mut String) {
 #[test]
 fn track_size() {
     let mut out = String::new();
+    write_size::<crate::autolink::AutoLinker>(&mut out);
     write_size::<crate::error::Error>(&mut out);
+    write_size::<crate::fmt::FormatOptions>(&mut out);
+    #[cfg(feature = "html")]
+    write_size::<crate::html::HtmlOptions>(&mut out);
+    write_size::<crate::links::LinkDefinition<'_>>(&mut out);
+    write_size::<crate::links::LinkResolver>(&mut out);
+    write_size::<crate::plain::PlainTextOptions>(&mut out);
+    write_size::<crate::references::References<'_>>(&mut out);
+    write_size::<crate::truncate::TruncateOptions>(&mut out);
     write_size::<crate::Release<'_>>(&mut out);
     write_size::<crate::Parser>(&mut out);
     test_helper::git::assert_diff(
@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use alloc::{string::String, vec::Vec};
+
+use pulldown_cmark::{CowStr, Event, HeadingLevel, Options, Tag, TagEnd};
+
+use crate::Release;
+
+/// Options for [`render_html`].
+///
+/// # Examples
+///
+/// ```
+/// use parse_changelog::HtmlOptions;
+///
+/// let changelog = parse_changelog::parse("## 0.1.0\n\n### Added\n\n- `foo`").unwrap();
+/// let html = parse_changelog::render_html(
+///     changelog.values(),
+///     HtmlOptions::new().heading_offset(1).anchors(true),
+/// );
+/// assert_eq!(
+///     html,
+///     "<h3 id=\"010\">0.1.0</h3>\n<h4>Added</h4>\n<ul>\n<li><code>foo</code></li>\n</ul>\n",
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HtmlOptions {
+    heading_offset: u8,
+    anchors: bool,
+    standalone: bool,
+}
+
+impl HtmlOptions {
+    /// Creates a new `HtmlOptions` with the default options.
+    ///
+    /// By default, release titles and headings in notes keep their levels in
+    /// the changelog, no anchors are added, and an HTML fragment (not a
+    /// complete HTML document) is output.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Demotes all headings (including release titles) by the given number of
+    /// levels. Headings that would exceed `<h6>` are rendered as `<h6>`.
+    pub fn heading_offset(&mut self, offset: u8) -> &mut Self {
+        self.heading_offset = offset;
+        self
+    }
+
+    /// If `true`, adds an `id` attribute to the heading of each release.
+    ///
+    /// The `id` is the same as the anchor of the release in the changelog
+    /// (see [`Release::anchor`]).
+    pub fn anchors(&mut self, anchors: bool) -> &mut Self {
+        self.anchors = anchors;
+        self
+    }
+
+    /// If `true`, outputs a complete HTML document instead of an HTML
+    /// fragment.
+    pub fn standalone(&mut self, standalone: bool) -> &mut Self {
+        self.standalone = standalone;
+        self
+    }
+}
+
+/// Renders the given releases from [CommonMark] to HTML.
+///
+/// Each release is rendered as a heading with the title of the release,
+/// followed by its notes. Links in titles are removed (see
+/// [`Release::title_no_link`]). In addition to CommonMark, tables,
+/// strikethrough, and task lists of [GitHub Flavored Markdown][gfm] are
+/// supported.
+///
+/// [CommonMark]: https://commonmark.org
+/// [gfm]: https://github.github.com/gfm
+pub fn render_html<'r, 'a: 'r, I>(releases: I, options: &HtmlOptions) -> String
+where
+    I: IntoIterator<Item = &'r Release<'a>>,
+{
+    let mut out = String::new();
+    let mut body = String::new();
+    let mut page_title = None;
+    for (i, release) in releases.into_iter().enumerate() {
+        let title = release.title_no_link();
+        let level = heading_level(release.heading_level() as usize, options.heading_offset);
+        let id = options.anchors.then(|| CowStr::from(release.anchor()));
+        page_title = if i == 0 { Some(title.clone()) } else { None };
+        let heading = [
+            Event::Start(Tag::Heading { level, id, classes: Vec::new(), attrs: Vec::new() }),
+            Event::Text(CowStr::Borrowed(&title)),
+            Event::End(TagEnd::Heading(level)),
+        ];
+        let notes =
+            pulldown_cmark::Parser::new_ext(release.notes, markdown_options()).map(|event| {
+                match event {
+                    Event::Start(Tag::Heading { level, id, classes, attrs }) => {
+                        let level = heading_level(level as usize, options.heading_offset);
+                        Event::Start(Tag::Heading { level, id, classes, attrs })
+                    }
+                    Event::End(TagEnd::Heading(level)) => Event::End(TagEnd::Heading(
+                        heading_level(level as usize, options.heading_offset),
+                    )),
+                    event => event,
+                }
+            });
+        pulldown_cmark::html::push_html(&mut body, heading.into_iter().chain(notes));
+    }
+    if !options.standalone {
+        return body;
+    }
+    let page_title = page_title.as_deref().unwrap_or("Changelog");
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>");
+    // Escape the title by rendering it as a text.
+    pulldown_cmark::html::push_html(&mut out, [Event::Text(page_title.into())].into_iter());
+    out.push_str("</title>\n</head>\n<body>\n");
+    out.push_str(&body);
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn markdown_options() -> Options {
+    Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS
+}
+
+fn heading_level(level: usize, offset: u8) -> HeadingLevel {
+    HeadingLevel::try_from((level + offset as usize).min(6)).unwrap()
+}
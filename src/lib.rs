@@ -67,6 +67,7 @@ assert_eq!(
 ### Optional features
 
 - **`serde`** — Implements [`serde::Serialize`](https://docs.rs/serde/latest/serde/trait.Serialize.html) trait for parse-changelog types.
+- **`html`** — Provides [`render_html`] function to render release notes as HTML.
 
 ## Supported Format
 
@@ -136,6 +137,8 @@ allowed as prefixes.
 
 To customize the prefix format, use the [`Parser::prefix_format`] method (library) or `--prefix-format` option (CLI).
 
+If the prefix format has a capture group named `package`, the captured text is treated as the package name of the release. This is useful for changelogs that contain release notes for multiple packages (e.g., `## my-crate 0.1.0`). Use the [`Parser::parse_packages`] method (library) or `--package` option (CLI) to get release notes of such changelogs.
+
 <!-- omit in toc -->
 #### Versions
 
@@ -176,6 +179,8 @@ You can freely include characters after the version.
 
 - [create-gh-release-action]: GitHub Action for creating GitHub Releases based on changelog. This action uses this crate for changelog parsing.
 
+[`render_html`]: https://docs.rs/parse-changelog/latest/parse_changelog/fn.render_html.html
+[`Parser::parse_packages`]: https://docs.rs/parse-changelog/latest/parse_changelog/struct.Parser.html#method.parse_packages
 [`Parser::prefix_format`]: https://docs.rs/parse-changelog/latest/parse_changelog/struct.Parser.html#method.prefix_format
 [`Parser::version_format`]: https://docs.rs/parse-changelog/latest/parse_changelog/struct.Parser.html#method.version_format
 [create-gh-release-action]: https://github.com/taiki-e/create-gh-release-action
@@ -227,10 +232,18 @@ mod serde_impl;
 #[path = "gen/tests/track_size.rs"]
 mod track_size;
 
+mod autolink;
 mod error;
-
-use alloc::{borrow::Cow, format, string::String};
-use core::mem;
+mod fmt;
+#[cfg(feature = "html")]
+mod html;
+mod links;
+mod plain;
+mod references;
+mod truncate;
+
+use alloc::{borrow::Cow, collections::BTreeMap, format, string::String, vec::Vec};
+use core::{mem, ops::Range};
 use std::sync::OnceLock;
 
 use indexmap::IndexMap;
@@ -238,6 +251,16 @@ use regex::Regex;
 
 pub use self::error::Error;
 use self::error::Result;
+#[cfg(feature = "html")]
+pub use self::html::{HtmlOptions, render_html};
+pub use self::{
+    autolink::AutoLinker,
+    fmt::FormatOptions,
+    links::{LinkDefinition, LinkResolver, link_definitions},
+    plain::PlainTextOptions,
+    references::References,
+    truncate::TruncateOptions,
+};
 
 /// A changelog.
 ///
@@ -252,6 +275,18 @@ use self::error::Result;
 /// [keepachangelog]: https://keepachangelog.com
 pub type Changelog<'a> = IndexMap<&'a str, Release<'a>>;
 
+/// A changelog that contains release notes for multiple packages.
+///
+/// The key is a pair of a package name and a version, and the value is the
+/// release note for that version of the package. The package name is `None` if
+/// the title of the release doesn't contain a package name.
+///
+/// The order is the same as the order written in the original text.
+///
+/// This type is returned by [`Parser::parse_packages`] method. See
+/// [`Parser::prefix_format`] for how to extract package names from titles.
+pub type PackageChangelog<'a> = IndexMap<(Option<&'a str>, &'a str), Release<'a>>;
+
 /// Parses release notes from the given `text`.
 ///
 /// This function uses the default version and prefix format. If you want to use
@@ -288,9 +323,53 @@ pub fn parse_iter(text: &str) -> ParseIter<'_, 'static> {
     ParseIter::new(text, None, None)
 }
 
-/// A release note for a version.
+/// A markdown heading (e.g., `### Fixed`).
+///
+/// This is returned by [`headings`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
+pub struct MarkdownHeading<'a> {
+    /// The level of the heading. 1-6
+    pub level: u8,
+    /// The text of the heading, without the closing sequence of Atx-style
+    /// headings.
+    pub title: &'a str,
+    /// The byte range of the heading, including the underline of Setext-style
+    /// headings and excluding the trailing newline.
+    pub range: Range<usize>,
+}
+
+/// Returns all headings in the given markdown text, in the order of appearance.
+///
+/// Unlike [`parse_iter`], this returns headings of any level and title.
+/// Headings in code blocks and HTML comments are ignored.
+///
+/// # Examples
+///
+/// ```
+/// let notes = "### Added\n\n- Foo\n\n```\n# Bar\n```\n\nFixed\n-----\n";
+/// let headings = parse_changelog::headings(notes);
+/// assert_eq!(headings.len(), 2);
+/// assert_eq!((headings[0].level, headings[0].title), (3, "Added"));
+/// assert_eq!((headings[1].level, headings[1].title), (2, "Fixed"));
+/// assert_eq!(&notes[headings[1].range.clone()], "Fixed\n-----");
+/// ```
+#[must_use]
+pub fn headings(text: &str) -> Vec<MarkdownHeading<'_>> {
+    let mut headings = Vec::new();
+    for_each_heading(text, |heading, range| {
+        let title = match heading.style {
+            HeadingStyle::Atx => fmt::strip_closing_sequence(heading.text),
+            HeadingStyle::Setext => heading.text,
+        };
+        headings.push(MarkdownHeading { level: heading.level, title, range });
+    });
+    headings
+}
+
+/// A release note for a version.
+#[derive(Clone)]
+#[non_exhaustive]
 pub struct Release<'a> {
     /// The version of this release.
     ///
@@ -317,6 +396,44 @@ pub struct Release<'a> {
     ///
     /// Note that leading and trailing newlines have been removed.
     pub notes: &'a str,
+    /// The package name of this release.
+    ///
+    /// ```text
+    /// ## my-crate 0.1.0 -- 2020-01-01
+    ///    ^^^^^^^^
+    /// ```
+    ///
+    /// This is the text captured by the group named `package` in the
+    /// [prefix format](Parser::prefix_format), and is `None` if the prefix
+    /// format has no such group or the group didn't match.
+    pub package: Option<&'a str>,
+    /// The text of the changelog up to the end of the heading of this
+    /// release. The position and level of the heading, and its anchor, are
+    /// computed from this on demand.
+    text: &'a str,
+}
+
+// The position of the heading is not part of the release: releases with the
+// same title and notes are equal even if they are at different positions.
+impl PartialEq for Release<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version
+            && self.title == other.title
+            && self.notes == other.notes
+            && self.package == other.package
+    }
+}
+impl Eq for Release<'_> {}
+
+impl core::fmt::Debug for Release<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Release")
+            .field("version", &self.version)
+            .field("title", &self.title)
+            .field("notes", &self.notes)
+            .field("package", &self.package)
+            .finish_non_exhaustive()
+    }
 }
 
 impl<'a> Release<'a> {
@@ -325,6 +442,254 @@ impl<'a> Release<'a> {
     pub fn title_no_link(&self) -> Cow<'a, str> {
         full_unlink(self.title)
     }
+
+    /// Returns the notes of this release as plain text.
+    ///
+    /// Markdown syntax is removed: links are rendered as `text (url)`,
+    /// emphasis and code markers are removed, and headings, list items, and
+    /// code blocks are kept as lines of text.
+    ///
+    /// This is equivalent to `self.notes_plain_with(&PlainTextOptions::new())`.
+    /// See [`notes_plain_with`](Self::notes_plain_with) for more options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let changelog = "## 0.1.0\n\n\
+    /// - Add **new** [`Parser`](https://docs.rs/parse-changelog) options.
+    /// ";
+    /// let changelog = parse_changelog::parse(changelog).unwrap();
+    /// assert_eq!(
+    ///     changelog["0.1.0"].notes_plain(),
+    ///     "- Add new Parser (https://docs.rs/parse-changelog) options.",
+    /// );
+    /// ```
+    #[must_use]
+    pub fn notes_plain(&self) -> String {
+        self.notes_plain_with(&PlainTextOptions::new())
+    }
+
+    /// Returns the notes of this release as plain text, with the given options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use parse_changelog::PlainTextOptions;
+    ///
+    /// let changelog = "## 0.1.0\n\n\
+    /// - Add **new** [`Parser`](https://docs.rs/parse-changelog) options to
+    ///   control how to parse changelogs.
+    /// ";
+    /// let changelog = parse_changelog::parse(changelog).unwrap();
+    /// assert_eq!(
+    ///     changelog["0.1.0"].notes_plain_with(PlainTextOptions::new().link_urls(false).width(40)),
+    ///     "- Add new Parser options to control how\n  to parse changelogs.",
+    /// );
+    /// ```
+    #[must_use]
+    pub fn notes_plain_with(&self, options: &PlainTextOptions) -> String {
+        plain::render(self.notes, options)
+    }
+
+    /// Returns issues, pull requests, commits, and users referenced in the
+    /// notes of this release.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let changelog = "## 0.1.0\n\n\
+    /// - Fix crash (#12, thanks @user)
+    /// - Fix [#13](https://github.com/owner/repo/pull/13) ([a1b2c3d](https://github.com/owner/repo/commit/a1b2c3d))
+    /// ";
+    /// let changelog = parse_changelog::parse(changelog).unwrap();
+    /// let references = changelog["0.1.0"].references();
+    /// assert_eq!(references.issues, [12, 13]);
+    /// assert_eq!(references.commits, ["a1b2c3d"]);
+    /// assert_eq!(references.mentions, ["user"]);
+    /// ```
+    #[must_use]
+    pub fn references(&self) -> References<'a> {
+        References::new(self.notes)
+    }
+
+    /// Returns the anchor of the heading of this release in the changelog.
+    ///
+    /// This is the same as the anchor GitHub generates when rendering the
+    /// changelog: the title with links removed is lowercased, punctuation is
+    /// removed, and spaces are replaced with `-`. If the same anchor is
+    /// already used by a preceding heading in the changelog (including
+    /// headings that are not release headings), `-N` suffix is appended.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let changelog = "## [1.2.0] - 2024-01-01\n\n### 1.1.0\n\n## 1.1.0\n\n\
+    ///                  [1.2.0]: https://github.com/owner/repo/compare/v1.1.0...v1.2.0\n";
+    /// let changelog = parse_changelog::parse(changelog).unwrap();
+    /// assert_eq!(changelog["1.2.0"].anchor(), "120---2024-01-01");
+    /// assert_eq!(changelog["1.1.0"].anchor(), "110-1");
+    /// ```
+    #[must_use]
+    pub fn anchor(&self) -> String {
+        // Anchors seen so far, and the number of their duplicates.
+        let mut anchors: BTreeMap<String, usize> = BTreeMap::new();
+        let mut unique = |base: String| {
+            let mut slug = base.clone();
+            let mut count = anchors.get(&base).copied().unwrap_or(0);
+            while anchors.contains_key(&slug) {
+                count += 1;
+                slug = format!("{base}-{count}");
+            }
+            if count != 0 {
+                anchors.insert(base, count);
+            }
+            anchors.insert(slug.clone(), 0);
+            slug
+        };
+        for_each_heading(&self.text[..self.heading_start()], |heading, _| {
+            unique(anchor(&full_unlink(heading.text)));
+        });
+        unique(anchor(&self.title_no_link()))
+    }
+
+    /// Returns the byte range of the heading of this release in the changelog.
+    ///
+    /// The range includes the underline of a Setext-style heading, and
+    /// doesn't include the trailing newline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let text = "# Changelog\n\n## 0.2.0\n\n- Foo\n\n0.1.0\n-----\n\n- Bar\n";
+    /// let changelog = parse_changelog::parse(text).unwrap();
+    /// assert_eq!(&text[changelog["0.2.0"].heading_range()], "## 0.2.0");
+    /// assert_eq!(&text[changelog["0.1.0"].heading_range()], "0.1.0\n-----");
+    /// ```
+    #[must_use]
+    pub fn heading_range(&self) -> Range<usize> {
+        self.heading_start()..self.text.len()
+    }
+
+    /// Returns the level of the heading of this release. 1-6
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let changelog = parse_changelog::parse("# Changelog\n\n## 0.1.0\n\n- Foo\n").unwrap();
+    /// assert_eq!(changelog["0.1.0"].heading_level(), 2);
+    ///
+    /// let changelog = parse_changelog::parse("0.1.0\n=====\n\n- Foo\n").unwrap();
+    /// assert_eq!(changelog["0.1.0"].heading_level(), 1);
+    /// ```
+    #[must_use]
+    pub fn heading_level(&self) -> u8 {
+        let (start, underline) = self.last_line();
+        let line = trim_start(&self.text[start..]);
+        match underline {
+            Some(b'=') => 1,
+            Some(_) => 2,
+            #[allow(clippy::cast_possible_truncation)] // heading levels are 1-6
+            None => line.bytes().take_while(|&b| b == b'#').count() as u8,
+        }
+    }
+
+    fn heading_start(&self) -> usize {
+        match self.last_line() {
+            // The heading starts at the line before the underline.
+            (start, Some(_)) => line_start(self.text, start - 1),
+            (start, None) => start,
+        }
+    }
+
+    /// Returns the start of the last line of the heading, and the character
+    /// of the underline if the heading is a Setext-style heading.
+    fn last_line(&self) -> (usize, Option<u8>) {
+        let start = line_start(self.text, self.text.len());
+        match trim_start(&self.text[start..]).as_bytes().first() {
+            Some(b'#') => (start, None),
+            c => (start, c.copied()),
+        }
+    }
+
+    /// Returns the notes of this release truncated to fit within the limits
+    /// specified by the given options.
+    ///
+    /// Notes are cut at the boundary of a block (e.g., a paragraph, a list
+    /// item, or a heading), and never inside a code block or a comment. If
+    /// [`changelog_url`](TruncateOptions::changelog_url) is set, a link to the
+    /// section of this release in the full changelog is appended to truncated
+    /// notes. Notes that already fit within the limits are returned as is.
+    ///
+    /// See [`TruncateOptions`] for examples.
+    #[must_use]
+    pub fn notes_truncated(&self, options: &TruncateOptions) -> Cow<'a, str> {
+        truncate::truncate(self, options)
+    }
+
+    /// Returns the notes of this release with HTML comments removed.
+    ///
+    /// Both inline and multi-line comments are removed. Lines that contain
+    /// only comments are removed entirely. Comments in code blocks are left
+    /// as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let changelog = "## 0.1.0\n\n<!-- TODO: add migration guide -->\n\n- Foo <!-- #12 -->\n";
+    /// let changelog = parse_changelog::parse(changelog).unwrap();
+    /// assert_eq!(changelog["0.1.0"].notes_without_comments(), "- Foo");
+    /// ```
+    #[must_use]
+    pub fn notes_without_comments(&self) -> Cow<'a, str> {
+        strip_comments(self.notes)
+    }
+
+    /// Returns the notes of this release with levels of all headings shifted
+    /// by `offset`.
+    ///
+    /// Levels are clamped to 1-6. Setext-style headings whose level changes
+    /// are converted to Atx-style headings. Headings in code blocks and
+    /// comments are left as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let changelog = "## 0.1.0\n\n### Added\n\n- Foo\n";
+    /// let changelog = parse_changelog::parse(changelog).unwrap();
+    /// assert_eq!(changelog["0.1.0"].notes_with_heading_offset(1), "#### Added\n\n- Foo");
+    /// assert_eq!(changelog["0.1.0"].notes_with_heading_offset(-1), "## Added\n\n- Foo");
+    /// ```
+    #[must_use]
+    pub fn notes_with_heading_offset(&self, offset: i8) -> Cow<'a, str> {
+        shift_headings(self.notes, offset.into())
+    }
+
+    /// Returns the notes of this release with levels of all headings shifted
+    /// so that the top-level heading in the notes has the given level.
+    ///
+    /// See [`notes_with_heading_offset`](Self::notes_with_heading_offset) for
+    /// details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let changelog = "## 0.1.0\n\n### Added\n\n#### Foo\n";
+    /// let changelog = parse_changelog::parse(changelog).unwrap();
+    /// assert_eq!(changelog["0.1.0"].notes_with_top_heading_level(1), "# Added\n\n## Foo");
+    /// ```
+    #[must_use]
+    pub fn notes_with_top_heading_level(&self, level: u8) -> Cow<'a, str> {
+        let mut top_level = None;
+        for_each_heading(self.notes, |heading, _| {
+            top_level = Some(top_level.map_or(heading.level, |l: u8| l.min(heading.level)));
+        });
+        match top_level {
+            Some(top_level) => {
+                shift_headings(self.notes, i16::from(level.clamp(1, 6)) - i16::from(top_level))
+            }
+            None => self.notes.into(),
+        }
+    }
 }
 
 /// A changelog parser.
@@ -418,6 +783,25 @@ impl Parser {
     /// ^(v|Version |Release )?
     /// ```
     ///
+    /// # Packages
+    ///
+    /// If the format has a capture group named `package`, the text captured by
+    /// that group is used as the [package name](Release::package) of the
+    /// release. This is useful for changelogs that contain release notes for
+    /// multiple packages. For example:
+    ///
+    /// ```
+    /// use parse_changelog::Parser;
+    ///
+    /// let text = "## my-crate 0.2.0\n\n- Bug fixes.\n\n## my-crate-macros 0.2.0\n\n- Added `foo` macro.\n";
+    /// let mut parser = Parser::new();
+    /// parser.prefix_format(r"^(?<package>[0-9A-Za-z_-]+) ")?;
+    /// let changelog = parser.parse_packages(text)?;
+    /// assert_eq!(changelog[&(Some("my-crate"), "0.2.0")].notes, "- Bug fixes.");
+    /// assert_eq!(changelog[&(Some("my-crate-macros"), "0.2.0")].notes, "- Added `foo` macro.");
+    /// # Ok::<(), parse_changelog::Error>(())
+    /// ```
+    ///
     /// # Errors
     ///
     /// Returns an error if any of the following:
@@ -465,6 +849,42 @@ impl Parser {
         Ok(map)
     }
 
+    /// Parses release notes for multiple packages from the given `text`.
+    ///
+    /// Unlike [`parse`] method, the returned map is keyed by a pair of a
+    /// [package name](Release::package) and a version, so the same version of
+    /// different packages can be in the same changelog. See
+    /// [`prefix_format`] for how to extract package names from titles.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the following:
+    ///
+    /// - There are multiple release notes for one version of one package.
+    /// - No release note was found. This usually means that the changelog isn't
+    ///   written in the supported format, or that the specified format is wrong
+    ///   if you specify your own format.
+    ///
+    /// [`parse`]: Self::parse
+    /// [`prefix_format`]: Self::prefix_format
+    pub fn parse_packages<'a>(&self, text: &'a str) -> Result<PackageChangelog<'a>> {
+        let mut map = IndexMap::new();
+        for release in self.parse_iter(text) {
+            if let Some(release) = map.insert((release.package, release.version), release) {
+                return Err(Error::parse(match release.package {
+                    Some(package) => {
+                        format!("multiple release notes for '{}' of '{package}'", release.version)
+                    }
+                    None => format!("multiple release notes for '{}'", release.version),
+                }));
+            }
+        }
+        if map.is_empty() {
+            return Err(Error::parse("no release note was found"));
+        }
+        Ok(map)
+    }
+
     /// Returns an iterator over all release notes in the given `text`.
     ///
     /// Unlike [`parse`] method, the returned iterator doesn't error on
@@ -477,6 +897,76 @@ impl Parser {
     pub fn parse_iter<'a, 'r>(&'r self, text: &'a str) -> ParseIter<'a, 'r> {
         ParseIter::new(text, self.version_format.as_ref(), self.prefix_format.as_ref())
     }
+
+    /// Returns the given changelog formatted in a consistent style.
+    ///
+    /// This does the following:
+    ///
+    /// - Converts level 1 and 2 headings to the
+    ///   [heading style](FormatOptions::heading_style), and removes closing
+    ///   `#` sequences of Atx-style headings.
+    /// - Puts exactly one blank line before and after each heading.
+    /// - Replaces markers of bullet list items with the
+    ///   [bullet](FormatOptions::bullet).
+    /// - Sorts each group of consecutive link reference definitions: labels
+    ///   of releases in the order of the releases first, and then other labels
+    ///   in alphabetical order.
+    /// - Sorts subsections of each release whose titles are
+    ///   [Keep a Changelog] categories in the canonical order (Added, Changed,
+    ///   Deprecated, Removed, Fixed, Security). Other subsections keep their
+    ///   positions.
+    /// - Removes trailing blank lines, and ends the text with a newline.
+    ///
+    /// Code blocks and HTML comments are left as is. Formatting is
+    /// idempotent: formatting the returned text again returns the same text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use parse_changelog::{FormatOptions, Parser};
+    ///
+    /// let changelog = "Changelog\n=========\n## 0.1.0\n### Fixed\n* Foo\n### Added\n* Bar\n";
+    /// assert_eq!(
+    ///     Parser::new().format(changelog, &FormatOptions::new()),
+    ///     "Changelog\n=========\n\n0.1.0\n-----\n\n### Added\n\n- Bar\n\n### Fixed\n\n- Foo\n",
+    /// );
+    /// ```
+    #[must_use]
+    pub fn format(&self, text: &str, options: &FormatOptions) -> String {
+        fmt::format(self, text, options)
+    }
+
+    /// Converts headings in the given changelog to the given style, leaving
+    /// the rest of the changelog as is.
+    ///
+    /// If `releases_only` is `true`, only release headings are converted.
+    ///
+    /// Only level 1 and 2 headings are converted, because Setext-style
+    /// headings only support levels 1 and 2. Headings in code blocks and HTML
+    /// comments are left as is. When an Atx-style heading just after a
+    /// paragraph is converted to a Setext-style heading, a blank line is
+    /// inserted before it so that it is not treated as a part of the paragraph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use parse_changelog::{HeadingStyle, Parser};
+    ///
+    /// let changelog = "Changelog\n=========\n\n0.1.0\n-----\n\n- Foo\n";
+    /// let parser = Parser::new();
+    /// assert_eq!(
+    ///     parser.convert_headings(changelog, HeadingStyle::Atx, false),
+    ///     "# Changelog\n\n## 0.1.0\n\n- Foo\n",
+    /// );
+    /// assert_eq!(
+    ///     parser.convert_headings(changelog, HeadingStyle::Atx, true),
+    ///     "Changelog\n=========\n\n## 0.1.0\n\n- Foo\n",
+    /// );
+    /// ```
+    #[must_use]
+    pub fn convert_headings(&self, text: &str, style: HeadingStyle, releases_only: bool) -> String {
+        fmt::convert_headings(self, text, style, releases_only)
+    }
 }
 
 /// An iterator over release notes.
@@ -487,6 +977,8 @@ impl Parser {
 pub struct ParseIter<'a, 'r> {
     version_format: &'r Regex,
     prefix_format: &'r Regex,
+    /// If `true`, `prefix_format` has a capture group named `package`.
+    has_package: bool,
     lines: Lines<'a>,
     /// The heading level of release sections. 1-6
     level: Option<u8>,
@@ -514,9 +1006,11 @@ impl<'a, 'r> ParseIter<'a, 'r> {
         version_format: Option<&'r Regex>,
         prefix_format: Option<&'r Regex>,
     ) -> Self {
+        let prefix_format = prefix_format.unwrap_or_else(|| default_prefix_format());
         Self {
             version_format: version_format.unwrap_or_else(|| default_version_format()),
-            prefix_format: prefix_format.unwrap_or_else(|| default_prefix_format()),
+            prefix_format,
+            has_package: prefix_format.capture_names().any(|name| name == Some("package")),
             lines: Lines::new(text),
             level: None,
         }
@@ -548,7 +1042,8 @@ impl<'a> Iterator for ParseIter<'a, '_> {
         // If `true`, we are in an inline comment (`... <!--`).
         let mut is_inline_comment = false;
         let mut release_note_start = None;
-        let mut cur_release = Release { version: "", title: "", notes: "" };
+        let mut cur_release =
+            Release { version: "", title: "", notes: "", package: None, text: "" };
 
         while let Some((line, line_start, line_end)) = self.lines.peek() {
             let line = trim_start(line);
@@ -558,31 +1053,13 @@ impl<'a> Iterator for ParseIter<'a, '_> {
                 heading(line, &mut self.lines)
             };
             let Some(heading) = heading else {
-                let line = line.as_bytes();
                 self.lines.next();
-                if let Some(fence) = on_code_block {
-                    if let Some(rest) = line.strip_prefix(fence) {
-                        let b = fence[0];
-                        if all_allow_end_spaces(rest, b) {
-                            on_code_block = None;
-                        }
-                    }
-                } else {
-                    if !on_comment {
-                        if let Some(&b @ (b'`' | b'~')) = line.first() {
-                            let mut len = 1;
-                            while line.get(len) == Some(&b) {
-                                len += 1;
-                            }
-                            if len >= 3 && (b != b'`' || !line[len..].contains(&b'`')) {
-                                on_code_block = Some(&line[..len]);
-                            }
-                        }
-                    }
-                    if on_code_block.is_none() {
-                        handle_comment(&mut on_comment, &mut is_inline_comment, line);
-                    }
-                }
+                handle_non_heading_line(
+                    &mut on_code_block,
+                    &mut on_comment,
+                    &mut is_inline_comment,
+                    line.as_bytes(),
+                );
 
                 // Non-heading lines are always considered part of the current
                 // section.
@@ -634,13 +1111,20 @@ impl<'a> Iterator for ParseIter<'a, '_> {
 
             cur_release.version = version;
             cur_release.title = heading.text;
+            if self.has_package {
+                cur_release.package = extract_package_from_title(heading.text, self.prefix_format);
+            }
             self.level.get_or_insert(heading.level);
 
+            let mut heading_end = line_end;
             self.lines.next();
             if heading.style == HeadingStyle::Setext {
                 // Skip an underline after a Setext-style heading.
-                self.lines.next();
+                if let Some((.., underline_end)) = self.lines.next() {
+                    heading_end = underline_end;
+                }
             }
+            cur_release.text = &self.lines.text[..heading_end];
             while let Some((next, ..)) = self.lines.peek() {
                 if next.trim_start().is_empty() {
                     // Skip newlines after a heading.
@@ -726,8 +1210,10 @@ struct Heading<'a> {
     style: HeadingStyle,
 }
 
-#[derive(PartialEq)]
-enum HeadingStyle {
+/// A style of markdown headings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HeadingStyle {
     /// Atx-style headings use 1-6 `#` characters at the start of the line,
     /// corresponding to header levels 1-6.
     Atx,
@@ -737,80 +1223,306 @@ enum HeadingStyle {
 }
 
 fn heading<'a>(line: &'a str, lines: &mut Lines<'a>) -> Option<Heading<'a>> {
-    if line.as_bytes().first() == Some(&b'#') {
-        let mut level = 1;
-        while level <= 7 && line.as_bytes().get(level) == Some(&b'#') {
-            level += 1;
+    if let Some(heading) = atx_heading(line) {
+        return Some(heading);
+    }
+    if let Some((next, ..)) = lines.peek2() {
+        if let Some(level) = setext_underline(next) {
+            return Some(Heading { text: line.trim_end(), level, style: HeadingStyle::Setext });
         }
-        // https://pandoc.org/try/?params=%7B%22text%22%3A%22%23%23%23%23%23%23%5Cn%3D%3D%3D%5Cn%5Cn%23%23%23%23%23%23%23%5Cn%3D%3D%3D%5Cn%5Cn%23%23%23%23%23%23+%5Cn%3D%3D%3D%5Cn%5Cn%23%23%23%23%23%23%5Ct%5Cn%3D%3D%3D%5Cn%5Cn%23%23%23%23%23%23+a%5Cn%3D%3D%3D%5Cn%5Cn%23%23%23%23%23%23%5Cta%5Cn%3D%3D%3D%5Cn%5Cn%23%23%23%23%23%23+b%5Cn%5Cn%22%2C%22to%22%3A%22html5%22%2C%22from%22%3A%22commonmark%22%2C%22standalone%22%3Afalse%2C%22embed-resources%22%3Afalse%2C%22table-of-contents%22%3Afalse%2C%22number-sections%22%3Afalse%2C%22citeproc%22%3Afalse%2C%22html-math-method%22%3A%22plain%22%2C%22wrap%22%3A%22auto%22%2C%22highlight-style%22%3Anull%2C%22files%22%3A%7B%7D%2C%22template%22%3Anull%7D
-        if level < 7 && line.as_bytes().get(level).is_none_or(|&b| matches!(b, b' ' | b'\t')) {
-            return Some(Heading {
-                text: line.get(level + 1..).map(str::trim).unwrap_or_default(),
-                #[allow(clippy::cast_possible_truncation)] // false positive: level is < 7: https://github.com/rust-lang/rust-clippy/issues/7486
-                level: level as u8,
-                style: HeadingStyle::Atx,
-            });
+    }
+    None
+}
+
+/// Parses an Atx-style heading. The closing sequence is not removed.
+fn atx_heading(line: &str) -> Option<Heading<'_>> {
+    if line.as_bytes().first() != Some(&b'#') {
+        return None;
+    }
+    let mut level = 1;
+    while level <= 7 && line.as_bytes().get(level) == Some(&b'#') {
+        level += 1;
+    }
+    // https://pandoc.org/try/?params=%7B%22text%22%3A%22%23%23%23%23%23%23%5Cn%3D%3D%3D%5Cn%5Cn%23%23%23%23%23%23%23%5Cn%3D%3D%3D%5Cn%5Cn%23%23%23%23%23%23+%5Cn%3D%3D%3D%5Cn%5Cn%23%23%23%23%23%23%5Ct%5Cn%3D%3D%3D%5Cn%5Cn%23%23%23%23%23%23+a%5Cn%3D%3D%3D%5Cn%5Cn%23%23%23%23%23%23%5Cta%5Cn%3D%3D%3D%5Cn%5Cn%23%23%23%23%23%23+b%5Cn%5Cn%22%2C%22to%22%3A%22html5%22%2C%22from%22%3A%22commonmark%22%2C%22standalone%22%3Afalse%2C%22embed-resources%22%3Afalse%2C%22table-of-contents%22%3Afalse%2C%22number-sections%22%3Afalse%2C%22citeproc%22%3Afalse%2C%22html-math-method%22%3A%22plain%22%2C%22wrap%22%3A%22auto%22%2C%22highlight-style%22%3Anull%2C%22files%22%3A%7B%7D%2C%22template%22%3Anull%7D
+    if level < 7 && line.as_bytes().get(level).is_none_or(|&b| matches!(b, b' ' | b'\t')) {
+        return Some(Heading {
+            text: line.get(level + 1..).map(str::trim).unwrap_or_default(),
+            #[allow(clippy::cast_possible_truncation)] // false positive: level is < 7: https://github.com/rust-lang/rust-clippy/issues/7486
+            level: level as u8,
+            style: HeadingStyle::Atx,
+        });
+    }
+    None
+}
+
+/// Returns the level of the Setext-style heading if `line` is its underline.
+fn setext_underline(line: &str) -> Option<u8> {
+    let line = trim_start_bytes(line.as_bytes());
+    let (&b @ (b'=' | b'-'), rest) = line.split_first()? else { return None };
+    all_allow_end_spaces(rest, b).then_some(if b == b'=' { 1 } else { 2 })
+}
+
+/// Updates the state of code blocks and comments with a non-heading line.
+fn handle_non_heading_line<'a>(
+    on_code_block: &mut Option<&'a [u8]>,
+    on_comment: &mut bool,
+    is_inline_comment: &mut bool,
+    line: &'a [u8],
+) {
+    if !handle_code_block(on_code_block, *on_comment, line) {
+        handle_comment(on_comment, is_inline_comment, line);
+    }
+}
+
+/// Updates the state of code blocks with a line, and returns `true` if the
+/// line is a part of a code block (including its opening and closing fences).
+fn handle_code_block<'a>(
+    on_code_block: &mut Option<&'a [u8]>,
+    on_comment: bool,
+    line: &'a [u8],
+) -> bool {
+    if let Some(fence) = *on_code_block {
+        if let Some(rest) = line.strip_prefix(fence) {
+            let b = fence[0];
+            if all_allow_end_spaces(rest, b) {
+                *on_code_block = None;
+            }
         }
+        return true;
     }
-    if let Some((next, ..)) = lines.peek2() {
-        let next = trim_start_bytes(next.as_bytes());
-        if let Some((&b @ (b'=' | b'-'), next)) = next.split_first() {
-            if all_allow_end_spaces(next, b) {
-                return Some(Heading {
-                    text: line.trim_end(),
-                    level: if b == b'=' { 1 } else { 2 },
-                    style: HeadingStyle::Setext,
-                });
+    if !on_comment {
+        if let Some(&b @ (b'`' | b'~')) = line.first() {
+            let mut len = 1;
+            while line.get(len) == Some(&b) {
+                len += 1;
+            }
+            if len >= 3 && (b != b'`' || !line[len..].contains(&b'`')) {
+                *on_code_block = Some(&line[..len]);
+                return true;
             }
         }
     }
-    None
+    false
 }
 
-fn handle_comment(on_comment: &mut bool, is_inline_comment: &mut bool, line: &[u8]) {
-    let mut first = true;
-    let mut line = Some(line);
-    while let Some(l) = line {
-        let Some(pos) = memchr::memchr(b'-', l) else { break };
-        match l.get(pos + 1) {
-            // --
-            // ^   pos
-            //  ^  pos + 1
-            Some(&b'-') => {}
-            Some(_) => {
-                line = l.get(pos + 2..);
-                first = false;
-                continue;
+/// Calls `f` with each heading in `text` and its range (including the
+/// underline of Setext-style headings). Headings in code blocks and comments
+/// are ignored.
+fn for_each_heading<'a>(text: &'a str, mut f: impl FnMut(&Heading<'a>, Range<usize>)) {
+    let mut on_code_block: Option<&[u8]> = None;
+    let mut on_comment = false;
+    let mut is_inline_comment = false;
+    let mut lines = Lines::new(text);
+    while let Some((line, line_start, line_end)) = lines.peek() {
+        let line = trim_start(line);
+        let heading = if on_code_block.is_some() || on_comment && !is_inline_comment {
+            None
+        } else {
+            heading(line, &mut lines)
+        };
+        lines.next();
+        match heading {
+            // An underline after a blank line is a thematic break.
+            Some(heading) if heading.style == HeadingStyle::Atx || !heading.text.is_empty() => {
+                on_comment = false;
+                let mut end = line_end;
+                if heading.style == HeadingStyle::Setext {
+                    // Skip an underline after a Setext-style heading.
+                    if let Some((.., underline_end)) = lines.next() {
+                        end = underline_end;
+                    }
+                }
+                f(&heading, line_start..end);
+            }
+            _ => handle_non_heading_line(
+                &mut on_code_block,
+                &mut on_comment,
+                &mut is_inline_comment,
+                line.as_bytes(),
+            ),
+        }
+    }
+}
+
+/// Shifts levels of headings in `text` by `offset`. Setext-style headings whose
+/// level changes are converted to Atx-style headings.
+fn shift_headings(text: &str, offset: i16) -> Cow<'_, str> {
+    if offset == 0 {
+        return text.into();
+    }
+    let mut buf = String::with_capacity(text.len() + 16);
+    let mut last = 0;
+    for_each_heading(text, |heading, range| {
+        let level = (i16::from(heading.level) + offset).clamp(1, 6);
+        let level = u8::try_from(level).unwrap();
+        if level == heading.level {
+            return;
+        }
+        buf.push_str(&text[last..range.start]);
+        for _ in 0..level {
+            buf.push('#');
+        }
+        if !heading.text.is_empty() {
+            buf.push(' ');
+            buf.push_str(heading.text);
+        }
+        last = range.end;
+    });
+    if last == 0 {
+        return text.into();
+    }
+    buf.push_str(&text[last..]);
+    buf.into()
+}
+
+/// Generates an anchor from the heading text in the same way as GitHub.
+pub(crate) fn anchor(text: &str) -> String {
+    let mut anchor = String::with_capacity(text.len());
+    for c in text.trim().chars() {
+        if c == ' ' {
+            anchor.push('-');
+        } else if c.is_alphanumeric() || c == '-' || c == '_' {
+            anchor.extend(c.to_lowercase());
+        }
+    }
+    anchor
+}
+
+/// Removes HTML comments outside code blocks from `text`.
+///
+/// Lines that contain only comments are removed, and blank lines left by
+/// them are collapsed.
+pub(crate) fn strip_comments(text: &str) -> Cow<'_, str> {
+    if !text.contains("<!--") {
+        return text.into();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut on_code_block: Option<&[u8]> = None;
+    let mut on_comment = false;
+    // If `true`, the previous line has been removed.
+    let mut removed = false;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        if handle_code_block(&mut on_code_block, on_comment, trim_start(content).as_bytes()) {
+            out.push_str(line);
+            removed = false;
+            continue;
+        }
+        if !on_comment && !content.contains("<!--") {
+            // A blank line after a removed line is redundant if the output is
+            // empty or already ends with a blank line.
+            if !(removed && content.trim().is_empty() && (out.is_empty() || out.ends_with("\n\n")))
+            {
+                out.push_str(line);
+                removed = false;
             }
-            None => break,
-        }
-        if let Some(open) = pos.checked_sub(2) {
-            if l.get(open) == Some(&b'<') && l.get(open + 1) == Some(&b'!') {
-                // <!--
-                // ^    open
-                //   ^  pos
-                if !*on_comment {
-                    *on_comment = true;
-                    *is_inline_comment = !first || open != 0;
+            continue;
+        }
+        let start = out.len();
+        // The start of the text to keep, if we are not in a comment.
+        let mut keep = (!on_comment).then_some(0);
+        scan_comments(&mut on_comment, content.as_bytes(), true, |delim, open| {
+            if open {
+                if let Some(keep) = keep.take() {
+                    out.push_str(&content[keep..delim.start]);
                 }
-                line = l.get(pos + 2..);
-                first = false;
-                continue;
+            } else {
+                let mut rest = &content[delim.end..];
+                if out[start..].ends_with([' ', '\t']) {
+                    // Avoid doubled spaces around removed inline comments.
+                    rest = rest.trim_start_matches([' ', '\t']);
+                }
+                keep = Some(content.len() - rest.len());
             }
+        });
+        if let Some(keep) = keep {
+            out.push_str(&content[keep..]);
         }
-        if let Some(close) = pos.checked_add(2) {
-            if l.get(close) == Some(&b'>') {
-                // -->
-                //   ^ close
-                // ^   pos
-                *on_comment = false;
-                line = l.get(close + 1..);
-                first = false;
-                continue;
+        if out[start..].trim().is_empty() {
+            out.truncate(start);
+            removed = true;
+        } else {
+            out.truncate(out.trim_end().len());
+            out.push_str(&line[content.len()..]);
+            removed = false;
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out.into()
+}
+
+fn handle_comment(on_comment: &mut bool, is_inline_comment: &mut bool, line: &[u8]) {
+    scan_comments(on_comment, line, false, |delim, open| {
+        if open {
+            *is_inline_comment = delim.start != 0;
+        }
+    });
+}
+
+/// Updates the state of comments with a line, calling `f` with the range of
+/// each comment delimiter (`<!--` or `-->`) that changes it and whether it
+/// opens a comment. If `code_spans` is `true`, delimiters in inline code spans
+/// are ignored.
+fn scan_comments(
+    on_comment: &mut bool,
+    line: &[u8],
+    code_spans: bool,
+    mut f: impl FnMut(Range<usize>, bool),
+) {
+    let mut i = 0;
+    // Backticks only need to be found if code spans are skipped.
+    let backtick = if code_spans { b'`' } else { b'-' };
+    while let Some(pos) = memchr::memchr2(b'-', backtick, &line[i..]) {
+        let pos = i + pos;
+        if line[pos] == b'`' {
+            let len = line[pos..].iter().take_while(|&&b| b == b'`').count();
+            i = pos + len;
+            if !*on_comment {
+                if let Some(end) = code_span_end(line, i, len) {
+                    i = end;
+                }
             }
+            continue;
+        }
+        i = pos + 1;
+        if line.get(pos + 1) != Some(&b'-') {
+            continue;
+        }
+        if !*on_comment && pos >= 2 && line[pos - 2..pos] == *b"<!" {
+            // <!--
+            //   ^  pos
+            *on_comment = true;
+            i = pos + 2;
+            f(pos - 2..i, true);
+        } else if *on_comment && line.get(pos + 2) == Some(&b'>') {
+            // -->
+            // ^   pos
+            *on_comment = false;
+            i = pos + 3;
+            f(pos..i, false);
+        }
+    }
+}
+
+/// Returns the end of the inline code span whose opening backtick string of
+/// length `len` ends at `start`, or `None` if it is not closed in `line`.
+fn code_span_end(line: &[u8], start: usize, len: usize) -> Option<usize> {
+    let mut i = start;
+    while let Some(pos) = memchr::memchr(b'`', &line[i..]) {
+        let pos = i + pos;
+        let n = line[pos..].iter().take_while(|&&b| b == b'`').count();
+        i = pos + n;
+        if n == len {
+            return Some(i);
         }
-        line = l.get(pos + 2..);
     }
+    None
+}
+
+/// Returns the start of the line that contains `text[pos]`, or the last line
+/// if `pos` is the end of `text`.
+fn line_start(text: &str, pos: usize) -> usize {
+    memchr::memrchr(b'\n', &text.as_bytes()[..pos]).map_or(0, |i| i + 1)
 }
 
 #[inline]
@@ -877,36 +1589,51 @@ fn extract_version_from_title<'a>(mut text: &'a str, prefix_format: &Regex) -> (
     unlink(text)
 }
 
+fn extract_package_from_title<'a>(text: &'a str, prefix_format: &Regex) -> Option<&'a str> {
+    // See extract_version_from_title for details.
+    let text = text.strip_prefix('[').unwrap_or(text);
+    let package = prefix_format.captures(text)?.name("package")?.as_str();
+    if package.is_empty() { None } else { Some(package) }
+}
+
 /// Remove a link from the given markdown text.
 ///
 /// # Note
 ///
 /// This is not a full "unlink" on markdown. See `full_unlink` for "full" version.
-fn unlink(mut s: &str) -> (&str, &str) {
+fn unlink(s: &str) -> (&str, &str) {
+    match split_link(s) {
+        Some((text, _, remaining)) => (text, remaining),
+        None => (s.strip_prefix('[').unwrap_or(s), ""),
+    }
+}
+
+/// Splits a link at the start of `s` into the link text, the link part
+/// (`(link)` or `[link]`, or empty if the link is a shortcut reference link),
+/// and the remaining text. Returns `None` if there is no closing bracket.
+fn split_link(mut s: &str) -> Option<(&str, &str, &str)> {
     // [1.0.0]
     // ^
     s = s.strip_prefix('[').unwrap_or(s);
-    if let Some(pos) = memchr::memchr(b']', s.as_bytes()) {
-        // 1.0.0]
-        //      ^
-        if pos + 1 == s.len() {
-            return (&s[..pos], "");
-        }
-        let remaining = &s[pos + 1..];
-        // 1.0.0](link)
-        //      ^^^^^^^
-        // 1.0.0][link]
-        //      ^^^^^^^
-        for (open, close) in [(b'(', b')'), (b'[', b']')] {
-            if remaining.as_bytes().first() == Some(&open) {
-                if let Some(r_pos) = memchr::memchr(close, &remaining.as_bytes()[1..]) {
-                    return (&s[..pos], &remaining[r_pos + 2..]);
-                }
+    let pos = memchr::memchr(b']', s.as_bytes())?;
+    // 1.0.0]
+    //      ^
+    if pos + 1 == s.len() {
+        return Some((&s[..pos], "", ""));
+    }
+    let remaining = &s[pos + 1..];
+    // 1.0.0](link)
+    //      ^^^^^^^
+    // 1.0.0][link]
+    //      ^^^^^^^
+    for (open, close) in [(b'(', b')'), (b'[', b']')] {
+        if remaining.as_bytes().first() == Some(&open) {
+            if let Some(r_pos) = memchr::memchr(close, &remaining.as_bytes()[1..]) {
+                return Some((&s[..pos], &remaining[..r_pos + 2], &remaining[r_pos + 2..]));
             }
         }
-        return (&s[..pos], remaining);
     }
-    (s, "")
+    Some((&s[..pos], "", remaining))
 }
 
 /// Remove links from the given markdown text.
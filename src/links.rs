@@ -0,0 +1,306 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Link reference definitions and rewriting of link destinations.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+
+use crate::{
+    code_span_end, handle_code_block, handle_comment, handle_non_heading_line, split_link,
+    trim_start,
+};
+
+/// A link reference definition (e.g., `[1.0.0]: https://example.com`).
+///
+/// This is returned by [`link_definitions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LinkDefinition<'a> {
+    /// The label of the definition, without brackets.
+    pub label: &'a str,
+    /// The destination of the definition, without angle brackets.
+    pub destination: &'a str,
+}
+
+/// Returns link reference definitions in the given markdown text, in the order
+/// of appearance.
+///
+/// Definitions in code blocks and HTML comments are ignored. The label and
+/// destination of each definition are subslices of `text`.
+///
+/// # Examples
+///
+/// ```
+/// let changelog = "## [1.0.0]\n\n[1.0.0]: https://github.com/owner/repo/releases/tag/v1.0.0\n";
+/// let definitions = parse_changelog::link_definitions(changelog);
+/// assert_eq!(definitions[0].label, "1.0.0");
+/// assert_eq!(definitions[0].destination, "https://github.com/owner/repo/releases/tag/v1.0.0");
+/// ```
+#[must_use]
+pub fn link_definitions(text: &str) -> Vec<LinkDefinition<'_>> {
+    let mut definitions = Vec::new();
+    let mut on_code_block: Option<&[u8]> = None;
+    let mut on_comment = false;
+    let mut is_inline_comment = false;
+    for line in text.lines() {
+        let line = trim_start(line);
+        if handle_code_block(&mut on_code_block, on_comment, line.as_bytes()) {
+            continue;
+        }
+        let prev_on_comment = on_comment;
+        handle_comment(&mut on_comment, &mut is_inline_comment, line.as_bytes());
+        if prev_on_comment {
+            continue;
+        }
+        definitions.extend(link_definition(line));
+    }
+    definitions
+}
+
+/// Parses a link reference definition that spans the given line.
+pub(crate) fn link_definition(line: &str) -> Option<LinkDefinition<'_>> {
+    // [label]: destination "title"
+    if !line.starts_with('[') {
+        return None;
+    }
+    let Some((label, "", rest)) = split_link(line) else { return None };
+    let rest = rest.strip_prefix(':')?;
+    if label.is_empty() || label.contains('[') {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let destination = match rest.strip_prefix('<') {
+        Some(r) => &r[..r.find('>')?],
+        None => &rest[..rest.find(char::is_whitespace).unwrap_or(rest.len())],
+    };
+    if destination.is_empty() && !rest.starts_with('<') {
+        return None;
+    }
+    Some(LinkDefinition { label, destination })
+}
+
+/// Resolves relative link destinations in markdown against a base URL.
+///
+/// This rewrites destinations of inline links (e.g., `[docs](docs/guide.md)`)
+/// and link reference definitions (e.g., `[docs]: docs/guide.md`). Absolute
+/// URLs, links to fragments in the same document (e.g., `#changes`), and
+/// links in code blocks are left as is.
+///
+/// As with links in HTML documents, the base URL is usually the URL of the
+/// changelog itself, and relative paths are resolved relative to the directory
+/// that contains it. `{rev}` in the base URL is replaced with the
+/// [revision](Self::revision) (`HEAD` by default).
+///
+/// # Examples
+///
+/// ```
+/// use parse_changelog::LinkResolver;
+///
+/// let notes = "- Fix [#12](../../pull/12). See [guide] for details.\n\n[guide]: docs/guide.md";
+/// let resolver = LinkResolver::new("https://github.com/owner/repo/blob/{rev}/CHANGELOG.md");
+/// assert_eq!(
+///     resolver.resolve(notes),
+///     "- Fix [#12](https://github.com/owner/repo/pull/12). See [guide] for details.\n\n\
+///      [guide]: https://github.com/owner/repo/blob/HEAD/docs/guide.md",
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct LinkResolver {
+    base_url: String,
+    revision: Option<String>,
+}
+
+impl LinkResolver {
+    /// Creates a new `LinkResolver` that resolves links against the given base URL.
+    #[must_use]
+    pub fn new(base_url: &str) -> Self {
+        Self { base_url: base_url.into(), revision: None }
+    }
+
+    /// Sets the revision that replaces `{rev}` in the base URL.
+    ///
+    /// If this is not set, `HEAD` is used.
+    pub fn revision(&mut self, revision: &str) -> &mut Self {
+        self.revision = Some(revision.into());
+        self
+    }
+
+    /// Returns the given text with relative link destinations resolved.
+    #[must_use]
+    pub fn resolve<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if memchr::memchr(b'[', text.as_bytes()).is_none() {
+            return Cow::Borrowed(text);
+        }
+        let base = self.base_url.replace("{rev}", self.revision.as_deref().unwrap_or("HEAD"));
+        let mut out = String::with_capacity(text.len() + text.len() / 4);
+        let mut on_code_block: Option<&[u8]> = None;
+        let mut on_comment = false;
+        let mut is_inline_comment = false;
+        for line in text.split_inclusive('\n') {
+            let prev_on_code_block = on_code_block.is_some();
+            handle_non_heading_line(
+                &mut on_code_block,
+                &mut on_comment,
+                &mut is_inline_comment,
+                trim_start(line.trim_end_matches('\n')).as_bytes(),
+            );
+            if prev_on_code_block || on_code_block.is_some() {
+                out.push_str(line);
+                continue;
+            }
+            resolve_line(line, &base, &mut out);
+        }
+        Cow::Owned(out)
+    }
+}
+
+fn resolve_line(line: &str, base: &str, out: &mut String) {
+    let trimmed = trim_start(line);
+    // [label]: destination "title"
+    if trimmed.starts_with('[') {
+        if let Some((_, "", remaining)) = split_link(trimmed) {
+            if let Some(rest) = remaining.strip_prefix(':') {
+                out.push_str(&line[..line.len() - rest.len()]);
+                push_resolved_destination(rest, base, out);
+                return;
+            }
+        }
+    }
+    let mut remaining = line;
+    while let Some(pos) = memchr::memchr2(b'[', b'`', remaining.as_bytes()) {
+        if remaining.as_bytes()[pos] == b'`' {
+            // Links in inline code spans are left as is.
+            let len = remaining[pos..].bytes().take_while(|&b| b == b'`').count();
+            let end = code_span_end(remaining.as_bytes(), pos + len, len).unwrap_or(pos + len);
+            out.push_str(&remaining[..end]);
+            remaining = &remaining[end..];
+            continue;
+        }
+        out.push_str(&remaining[..pos]);
+        let Some((text, link, r)) = split_link(&remaining[pos..]) else {
+            remaining = &remaining[pos..];
+            break;
+        };
+        out.push('[');
+        out.push_str(text);
+        out.push(']');
+        match link.strip_prefix('(') {
+            Some(inner) => {
+                // (destination "title")
+                out.push('(');
+                push_resolved_destination(&inner[..inner.len() - 1], base, out);
+                out.push(')');
+            }
+            None => out.push_str(link),
+        }
+        remaining = r;
+    }
+    out.push_str(remaining);
+}
+
+/// Pushes `s` to `out` with the link destination at the start of `s` resolved.
+fn push_resolved_destination(s: &str, base: &str, out: &mut String) {
+    let rest = s.trim_start();
+    out.push_str(&s[..s.len() - rest.len()]);
+    let (dest, rest) = if let Some(r) = rest.strip_prefix('<') {
+        match r.find('>') {
+            Some(end) => {
+                out.push('<');
+                (&r[..end], &r[end..])
+            }
+            None => ("", rest),
+        }
+    } else {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        (&rest[..end], &rest[end..])
+    };
+    resolve_url(base, dest, out);
+    out.push_str(rest);
+}
+
+fn has_scheme(url: &str) -> bool {
+    match url.find([':', '/', '?', '#']) {
+        Some(pos) if url.as_bytes()[pos] == b':' => {
+            let scheme = &url[..pos];
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'))
+        }
+        _ => false,
+    }
+}
+
+/// Resolves `url` against `base` as described in [RFC 3986], and pushes the
+/// result to `out`.
+///
+/// [RFC 3986]: https://datatracker.ietf.org/doc/html/rfc3986#section-5.2
+fn resolve_url(base: &str, url: &str, out: &mut String) {
+    if url.is_empty() || url.starts_with('#') || has_scheme(url) || !has_scheme(base) {
+        out.push_str(url);
+        return;
+    }
+    // scheme://authority/path?query#fragment
+    let scheme_end = base.find(':').unwrap();
+    let after_scheme = &base[scheme_end + 1..];
+    let authority_len = match after_scheme.strip_prefix("//") {
+        Some(a) => 2 + a.find(['/', '?', '#']).unwrap_or(a.len()),
+        None => 0,
+    };
+    let origin = &base[..scheme_end + 1 + authority_len];
+    let base_path = &after_scheme[authority_len..];
+    let base_path = &base_path[..base_path.find(['?', '#']).unwrap_or(base_path.len())];
+
+    if url.starts_with("//") {
+        out.push_str(&base[..=scheme_end]);
+        out.push_str(url);
+        return;
+    }
+    if url.starts_with('?') {
+        out.push_str(origin);
+        out.push_str(base_path);
+        out.push_str(url);
+        return;
+    }
+    let path_end = url.find(['?', '#']).unwrap_or(url.len());
+    let (path, suffix) = url.split_at(path_end);
+    out.push_str(origin);
+    if path.starts_with('/') {
+        remove_dot_segments(path, out);
+    } else {
+        let mut merged = String::with_capacity(base_path.len() + path.len() + 1);
+        match base_path.rfind('/') {
+            Some(pos) => merged.push_str(&base_path[..=pos]),
+            None if authority_len != 0 => merged.push('/'),
+            None => {}
+        }
+        merged.push_str(path);
+        remove_dot_segments(&merged, out);
+    }
+    out.push_str(suffix);
+}
+
+fn remove_dot_segments(path: &str, out: &mut String) {
+    let (leading_slash, path) = match path.strip_prefix('/') {
+        Some(path) => (true, path),
+        None => (false, path),
+    };
+    let mut segments: Vec<&str> = Vec::new();
+    let mut trailing_slash = false;
+    for segment in path.split('/') {
+        trailing_slash = matches!(segment, "." | "..");
+        match segment {
+            "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    if trailing_slash {
+        segments.push("");
+    }
+    if leading_slash {
+        out.push('/');
+    }
+    out.push_str(&segments.join("/"));
+}
@@ -0,0 +1,470 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Rendering of markdown as plain text.
+//
+// This is not a full CommonMark implementation. It handles the constructs
+// commonly used in changelogs: headings, lists, block quotes, fenced code
+// blocks, links, emphasis, code spans, and HTML comments.
+
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::{
+    atx_heading, autolink::is_boundary, fmt::strip_closing_sequence, handle_code_block,
+    links::link_definition, setext_underline, trim_start,
+};
+
+/// Options for [`Release::notes_plain_with`](crate::Release::notes_plain_with).
+#[derive(Debug, Clone)]
+pub struct PlainTextOptions {
+    link_urls: bool,
+    width: Option<usize>,
+}
+
+impl Default for PlainTextOptions {
+    fn default() -> Self {
+        Self { link_urls: true, width: None }
+    }
+}
+
+impl PlainTextOptions {
+    /// Creates a new `PlainTextOptions` with the default options.
+    ///
+    /// By default, links are rendered as `text (url)` and line breaks in the
+    /// original text are kept.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// If `true`, links are rendered as `text (url)`. If `false`, links are
+    /// rendered as just `text`.
+    pub fn link_urls(&mut self, link_urls: bool) -> &mut Self {
+        self.link_urls = link_urls;
+        self
+    }
+
+    /// Reflows paragraphs and list items to the given width.
+    ///
+    /// Words longer than the width are not split. Code blocks are never
+    /// reflowed.
+    pub fn width(&mut self, width: usize) -> &mut Self {
+        self.width = Some(width);
+        self
+    }
+}
+
+enum Block<'a> {
+    Blank,
+    /// A line that is output as is. (e.g., a line in code blocks)
+    Verbatim(&'a str),
+    /// A paragraph, a list item, or a heading.
+    Text {
+        /// The prefix of the first line. (e.g., indentation and list marker)
+        prefix: String,
+        /// Lines of the text, without prefix and indentation.
+        lines: Vec<&'a str>,
+        /// `false` if subsequent lines cannot be merged to this block.
+        open: bool,
+    },
+}
+
+pub(crate) fn render(notes: &str, options: &PlainTextOptions) -> String {
+    let notes = crate::strip_comments(notes);
+    let (blocks, references) = blocks(&notes);
+    let mut out = String::with_capacity(notes.len());
+    for block in &blocks {
+        match block {
+            Block::Blank => {
+                if !out.is_empty() && !out.ends_with("\n\n") {
+                    out.push('\n');
+                }
+            }
+            Block::Verbatim(line) => {
+                out.push_str(line.trim_end());
+                out.push('\n');
+            }
+            Block::Text { prefix, lines, .. } => {
+                let mut text = String::new();
+                render_inline(&lines.join("\n"), options.link_urls, &references, &mut text);
+                let indent: String = prefix.chars().map(|_| ' ').collect();
+                if text.trim().is_empty() {
+                    // An empty list item.
+                    out.push_str(prefix.trim_end());
+                    out.push('\n');
+                    continue;
+                }
+                match options.width {
+                    Some(width) => wrap(&text, prefix, &indent, width, &mut out),
+                    None => {
+                        for (i, line) in text.lines().enumerate() {
+                            out.push_str(if i == 0 { prefix } else { &indent });
+                            out.push_str(line.trim());
+                            out.push('\n');
+                        }
+                    }
+                }
+            }
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+/// Splits notes into blocks, and collects link reference definitions.
+fn blocks(notes: &str) -> (Vec<Block<'_>>, Vec<(String, &str)>) {
+    let mut blocks: Vec<Block<'_>> = Vec::new();
+    let mut references = Vec::new();
+    let mut on_code_block: Option<&[u8]> = None;
+    for line in notes.lines() {
+        let prev_on_code_block = on_code_block.is_some();
+        // Comments have already been removed.
+        if handle_code_block(&mut on_code_block, false, trim_start(line).as_bytes()) {
+            // Fences are not output.
+            if prev_on_code_block && on_code_block.is_some() {
+                blocks.push(Block::Verbatim(line));
+            }
+            continue;
+        }
+        let trimmed = line.trim_start();
+
+        if trimmed.trim_end().is_empty() {
+            blocks.push(Block::Blank);
+            continue;
+        }
+        // Block quotes.
+        let mut content = trimmed;
+        while let Some(rest) = content.strip_prefix('>') {
+            content = rest.trim_start();
+        }
+        let indent = &line[..line.len() - trimmed.len()];
+
+        // Setext-style heading underline.
+        if let Some(Block::Text { prefix, open, .. }) = blocks.last_mut() {
+            if *open
+                && setext_underline(content).is_some()
+                // `-` after a list item is an empty list item.
+                && (prefix.trim().is_empty() || list_marker(content).is_none())
+            {
+                *open = false;
+                continue;
+            }
+        }
+        // Atx-style heading.
+        if let Some(heading) = atx_heading(content) {
+            let text = strip_closing_sequence(heading.text);
+            blocks.push(Block::Text { prefix: String::new(), lines: vec![text], open: false });
+            continue;
+        }
+        // Link reference definition.
+        if let Some(definition) = link_definition(content) {
+            references.push((definition.label.to_lowercase(), definition.destination));
+            continue;
+        }
+        // List item.
+        if let Some((marker, rest)) = list_marker(content) {
+            let mut prefix = String::from(indent);
+            prefix.push_str(marker);
+            prefix.push(' ');
+            blocks.push(Block::Text { prefix, lines: vec![rest], open: true });
+            continue;
+        }
+        // Paragraph continuation.
+        if let Some(Block::Text { lines, open: true, .. }) = blocks.last_mut() {
+            lines.push(content);
+            continue;
+        }
+        blocks.push(Block::Text { prefix: indent.into(), lines: vec![content], open: true });
+    }
+    (blocks, references)
+}
+
+/// Parses a list marker, and returns the normalized marker and the rest.
+pub(crate) fn list_marker(s: &str) -> Option<(&str, &str)> {
+    let bytes = s.as_bytes();
+    let marker_len = match bytes.first()? {
+        b'-' | b'*' | b'+' => 1,
+        b'0'..=b'9' => {
+            let digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+            if !matches!(bytes.get(digits), Some(b'.' | b')')) {
+                return None;
+            }
+            digits + 1
+        }
+        _ => return None,
+    };
+    let rest = &s[marker_len..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let marker = if marker_len == 1 { "-" } else { &s[..marker_len] };
+    Some((marker, rest.trim()))
+}
+
+fn render_inline(s: &str, link_urls: bool, references: &[(String, &str)], out: &mut String) {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < s.len() {
+        let c = bytes[i];
+        match c {
+            b'\\' if bytes.get(i + 1).is_some_and(u8::is_ascii_punctuation) => {
+                out.push(bytes[i + 1] as char);
+                i += 2;
+            }
+            b'`' => {
+                let n = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+                let fence = &s[i..i + n];
+                match find_code_span_end(&s[i + n..], fence) {
+                    Some(end) => {
+                        let code = &s[i + n..i + n + end];
+                        let code = if code.len() >= 2
+                            && code.starts_with(' ')
+                            && code.ends_with(' ')
+                            && !code.trim().is_empty()
+                        {
+                            &code[1..code.len() - 1]
+                        } else {
+                            code
+                        };
+                        out.push_str(code);
+                        i += n + end + n;
+                    }
+                    None => {
+                        out.push_str(fence);
+                        i += n;
+                    }
+                }
+            }
+            b'!' if bytes.get(i + 1) == Some(&b'[') => {
+                match link(&s[i + 1..], link_urls, references, out) {
+                    Some(len) => i += 1 + len,
+                    None => {
+                        out.push('!');
+                        i += 1;
+                    }
+                }
+            }
+            b'[' => match link(&s[i..], link_urls, references, out) {
+                Some(len) => i += len,
+                None => {
+                    out.push('[');
+                    i += 1;
+                }
+            },
+            b'<' => match autolink(&s[i..]) {
+                Some(url) => {
+                    out.push_str(url);
+                    i += url.len() + 2;
+                }
+                None => {
+                    out.push('<');
+                    i += 1;
+                }
+            },
+            // https://example.com/_foo_
+            b'h' if is_url(s, i) => {
+                let len = url_len(&s[i..]);
+                out.push_str(&s[i..i + len]);
+                i += len;
+            }
+            b'*' | b'_' | b'~' => {
+                let n = bytes[i..].iter().take_while(|&&b| b == c).count();
+                let (can_open, _) =
+                    flanking(c, s[..i].chars().next_back(), s[i + n..].chars().next());
+                // Delimiters that are not closed are output as is.
+                let can_open = can_open && (c != b'~' || n <= 2);
+                match can_open.then(|| closing_delimiter(&s[i + n..], c, n)).flatten() {
+                    Some(end) => {
+                        render_inline(&s[i + n..i + n + end], link_urls, references, out);
+                        i += n + end + n;
+                    }
+                    None => {
+                        out.push_str(&s[i..i + n]);
+                        i += n;
+                    }
+                }
+            }
+            _ => {
+                let ch = s[i..].chars().next().unwrap();
+                out.push(ch);
+                i += ch.len_utf8();
+            }
+        }
+    }
+}
+
+/// Returns whether a delimiter run of `delim` characters between `prev` and
+/// `next` can open and close emphasis (or strikethrough).
+fn flanking(delim: u8, prev: Option<char>, next: Option<char>) -> (bool, bool) {
+    let is_space = |c: Option<char>| c.is_none_or(char::is_whitespace);
+    let is_punct = |c: Option<char>| c.is_some_and(|c| c.is_ascii_punctuation());
+    let left = !is_space(next) && (!is_punct(next) || is_space(prev) || is_punct(prev));
+    let right = !is_space(prev) && (!is_punct(prev) || is_space(next) || is_punct(next));
+    if delim == b'_' {
+        // Intraword `_` is not emphasis.
+        (left && (!right || is_punct(prev)), right && (!left || is_punct(next)))
+    } else {
+        (left, right)
+    }
+}
+
+/// Returns the offset of the run of `len` `delim` characters that closes
+/// emphasis (or strikethrough) opened before `s`.
+fn closing_delimiter(s: &str, delim: u8, len: usize) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut pos = 0;
+    while pos < s.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            b'`' => {
+                let fence_len = bytes[pos..].iter().take_while(|&&b| b == b'`').count();
+                let fence = &s[pos..pos + fence_len];
+                pos += fence_len;
+                pos += find_code_span_end(&s[pos..], fence).map_or(0, |end| end + fence_len);
+            }
+            b'h' if is_url(s, pos) => pos += url_len(&s[pos..]),
+            b if b == delim => {
+                let run_len = bytes[pos..].iter().take_while(|&&b| b == delim).count();
+                let prev = s[..pos].chars().next_back();
+                let next = s[pos + run_len..].chars().next();
+                if run_len == len && flanking(delim, prev, next).1 {
+                    return Some(pos);
+                }
+                pos += run_len;
+            }
+            _ => pos += 1,
+        }
+    }
+    None
+}
+
+/// Returns `true` if a bare URL (e.g., `https://example.com`) starts at `s[i]`.
+fn is_url(s: &str, i: usize) -> bool {
+    let rest = &s[i..];
+    is_boundary(s[..i].chars().next_back())
+        && (rest.starts_with("https://") || rest.starts_with("http://"))
+}
+
+/// Returns the length of the bare URL at the beginning of `s`. Trailing
+/// punctuation is not part of the URL.
+fn url_len(s: &str) -> usize {
+    let url = &s[..s.find(|c: char| c.is_whitespace() || c == '<').unwrap_or(s.len())];
+    url.trim_end_matches(['.', ',', ':', ';', '!', '?', '*', '_', '~', '\'', '"']).len()
+}
+
+/// Returns the offset of the closing backtick string.
+fn find_code_span_end(s: &str, fence: &str) -> Option<usize> {
+    let mut pos = 0;
+    while let Some(p) = s[pos..].find(fence) {
+        let start = pos + p;
+        let end = start + fence.len();
+        // The closing backtick string must have the same length.
+        if !s[end..].starts_with('`') {
+            return Some(start);
+        }
+        pos = end + s[end..].bytes().take_while(|&b| b == b'`').count();
+    }
+    None
+}
+
+/// Renders a link that starts at the beginning of `s`, and returns its
+/// length. Returns `None` if `s` doesn't start with a link.
+fn link(
+    s: &str,
+    link_urls: bool,
+    references: &[(String, &str)],
+    out: &mut String,
+) -> Option<usize> {
+    let text_end = matching(s, b'[', b']')?;
+    let text = &s[1..text_end];
+    let rest = &s[text_end + 1..];
+    let (dest, len) = if rest.starts_with('(') {
+        let end = matching(rest, b'(', b')')?;
+        let inner = rest[1..end].trim();
+        // Remove the link title.
+        let dest = inner.split_whitespace().next().unwrap_or("");
+        (Some(dest.trim_start_matches('<').trim_end_matches('>')), text_end + 1 + end + 1)
+    } else if rest.starts_with('[') {
+        let end = matching(rest, b'[', b']')?;
+        let label = if end == 1 { text } else { &rest[1..end] };
+        (find_reference(references, label), text_end + 1 + end + 1)
+    } else {
+        (find_reference(references, text), text_end + 1)
+    };
+    let start = out.len();
+    render_inline(text, link_urls, references, out);
+    if let Some(dest) = dest {
+        if link_urls && !dest.is_empty() && out[start..] != *dest {
+            out.push_str(" (");
+            out.push_str(dest);
+            out.push(')');
+        }
+    }
+    Some(len)
+}
+
+fn find_reference<'a>(references: &[(String, &'a str)], label: &str) -> Option<&'a str> {
+    let label = label.to_lowercase();
+    references.iter().find(|(l, _)| *l == label).map(|&(_, dest)| dest)
+}
+
+/// Returns the offset of the bracket that matches the opening bracket at the
+/// beginning of `s`.
+fn matching(s: &str, open: u8, close: u8) -> Option<usize> {
+    let mut depth = 0_usize;
+    let mut escaped = false;
+    for (i, &b) in s.as_bytes().iter().enumerate() {
+        if escaped {
+            escaped = false;
+        } else if b == b'\\' {
+            escaped = true;
+        } else if b == open {
+            depth += 1;
+        } else if b == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        } else if b == b'\n' && open == b'(' {
+            return None;
+        }
+    }
+    None
+}
+
+/// Parses an autolink (e.g., `<https://example.com>`), and returns the URL.
+fn autolink(s: &str) -> Option<&str> {
+    let end = s.find('>')?;
+    let url = &s[1..end];
+    let (scheme, _) = url.split_once(':')?;
+    let valid = (2..=32).contains(&scheme.len())
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'.' | b'-'))
+        && !url.contains(|c: char| c.is_whitespace() || c == '<');
+    valid.then_some(url)
+}
+
+/// Wraps the text to the given width.
+fn wrap(text: &str, prefix: &str, indent: &str, width: usize, out: &mut String) {
+    let mut line_len = 0;
+    let mut first = true;
+    for word in text.split_whitespace() {
+        let word_len = word.chars().count();
+        if first {
+            out.push_str(prefix);
+            line_len = prefix.chars().count();
+        } else if line_len + 1 + word_len > width {
+            out.push('\n');
+            out.push_str(indent);
+            line_len = indent.chars().count();
+        } else {
+            out.push(' ');
+            line_len += 1;
+        }
+        if first {
+            first = false;
+        }
+        out.push_str(word);
+        line_len += word_len;
+    }
+    out.push('\n');
+}
@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Extraction of references to issues, commits, and users.
+
+use alloc::vec::Vec;
+
+use crate::{
+    autolink::{self, Part, Reference},
+    split_link,
+};
+
+/// Issues, pull requests, commits, and users referenced in release notes.
+///
+/// This is returned by [`Release::references`](crate::Release::references).
+///
+/// Each list is in the order of first appearance and has no duplicates.
+/// References in code blocks and HTML comments are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct References<'a> {
+    /// Numbers of issues and pull requests.
+    ///
+    /// This includes `#N`, `GH-N`, `owner/repo#N`, and links to
+    /// `.../issues/N`, `.../pull/N`, and `.../merge_requests/N`. Since the
+    /// repository of the changelog is unknown, references to other
+    /// repositories are included in the same way, whether written as
+    /// `owner/repo#N` or as links.
+    pub issues: Vec<u64>,
+    /// Commit hashes.
+    ///
+    /// This includes abbreviated or full hashes (e.g., `a1b2c3d`), and links
+    /// to `.../commit/<hash>`.
+    pub commits: Vec<&'a str>,
+    /// Mentioned users (`@user`), without `@`.
+    pub mentions: Vec<&'a str>,
+}
+
+impl<'a> References<'a> {
+    pub(crate) fn new(notes: &'a str) -> Self {
+        let mut references = Self::default();
+        autolink::walk(notes, |part, s| match part {
+            Part::Text => references.scan_text(s),
+            Part::Code => {
+                // `a1b2c3d`
+                if s.starts_with('`') {
+                    references.push_commit(s.trim_matches('`').trim());
+                }
+            }
+            Part::Link => {
+                let Some((text, link, remaining)) = split_link(s.trim_start()) else { return };
+                let text = text.trim_matches('`');
+                if !references.push_commit(text) {
+                    references.scan_text(text);
+                }
+                let url = match link.strip_prefix('(') {
+                    // [text](url "title")
+                    Some(inner) => inner[..inner.len() - 1].split_whitespace().next(),
+                    // [label]: url "title"
+                    None => remaining.strip_prefix(':').and_then(|r| r.split_whitespace().next()),
+                };
+                if let Some(url) = url {
+                    references.scan_url(url.trim_start_matches('<').trim_end_matches('>'));
+                }
+            }
+            Part::Verbatim => {
+                let url = match s.strip_prefix('<') {
+                    Some(s) => s.trim_end_matches('>'),
+                    // Trailing punctuation is not part of bare URLs.
+                    None => s.trim_end_matches(['.', ',', ':', ';', '!', '?', ')']),
+                };
+                if url.starts_with("https://") || url.starts_with("http://") {
+                    references.scan_url(url);
+                }
+            }
+        });
+        references
+    }
+
+    fn scan_text(&mut self, s: &'a str) {
+        let mut i = 0;
+        while i < s.len() {
+            let rest = &s[i..];
+            if autolink::is_boundary(s[..i].chars().next_back()) {
+                if let Some((reference, len)) = autolink::reference(rest) {
+                    match reference {
+                        Reference::Issue { number, .. } => self.push_issue(number),
+                        Reference::User(user) => {
+                            if !self.mentions.contains(&user) {
+                                self.mentions.push(user);
+                            }
+                        }
+                    }
+                    i += len;
+                    continue;
+                }
+                let word_len = rest.bytes().take_while(u8::is_ascii_alphanumeric).count();
+                if word_len != 0 {
+                    self.push_commit(&rest[..word_len]);
+                    i += word_len;
+                    continue;
+                }
+            }
+            i += rest.chars().next().unwrap().len_utf8();
+        }
+    }
+
+    fn scan_url(&mut self, url: &'a str) {
+        let url = &url[..url.find(['?', '#']).unwrap_or(url.len())];
+        let mut segments = url.split('/');
+        while let Some(segment) = segments.next() {
+            match segment {
+                "issues" | "pull" | "pulls" | "merge_requests" => {
+                    if let Some(number) = segments.next() {
+                        if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) {
+                            self.push_issue(number);
+                        }
+                    }
+                }
+                "commit" | "commits" => {
+                    if let Some(hash) = segments.next() {
+                        self.push_commit(hash);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn push_issue(&mut self, number: &str) {
+        if let Ok(number) = number.parse() {
+            if !self.issues.contains(&number) {
+                self.issues.push(number);
+            }
+        }
+    }
+
+    /// Pushes `s` to commits if it looks like a commit hash, and returns
+    /// `true` if it does.
+    fn push_commit(&mut self, s: &'a str) -> bool {
+        // To avoid false positives, a hash must contain both digits and
+        // letters, and must be lowercase.
+        let is_hash = (7..=40).contains(&s.len())
+            && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+            && s.bytes().any(|b| b.is_ascii_digit())
+            && s.bytes().any(|b| b.is_ascii_alphabetic());
+        if is_hash && !self.commits.contains(&s) {
+            self.commits.push(s);
+        }
+        is_hash
+    }
+}
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Truncation of release notes.
+
+use alloc::{borrow::Cow, format, string::String};
+
+use crate::{Release, handle_non_heading_line, plain, trim_start};
+
+const DEFAULT_LINK_TEXT: &str = "See the full changelog";
+
+/// Options for [`Release::notes_truncated`](crate::Release::notes_truncated).
+///
+/// # Examples
+///
+/// ```
+/// use parse_changelog::TruncateOptions;
+///
+/// let changelog = "## 0.1.0\n\n- Foo\n- Bar\n- Baz\n- Qux\n";
+/// let changelog = parse_changelog::parse(changelog).unwrap();
+/// assert_eq!(
+///     changelog["0.1.0"].notes_truncated(
+///         TruncateOptions::new()
+///             .max_lines(3)
+///             .changelog_url("https://github.com/owner/repo/blob/HEAD/CHANGELOG.md"),
+///     ),
+///     "- Foo\n\n\
+///      [See the full changelog](https://github.com/owner/repo/blob/HEAD/CHANGELOG.md#010)",
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TruncateOptions {
+    max_bytes: Option<usize>,
+    max_lines: Option<usize>,
+    changelog_url: Option<String>,
+    link_text: Option<String>,
+}
+
+impl TruncateOptions {
+    /// Creates a new `TruncateOptions` with the default options.
+    ///
+    /// By default, there are no limits and notes are never truncated.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum length of the notes in bytes.
+    pub fn max_bytes(&mut self, max_bytes: usize) -> &mut Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Sets the maximum number of lines of the notes.
+    pub fn max_lines(&mut self, max_lines: usize) -> &mut Self {
+        self.max_lines = Some(max_lines);
+        self
+    }
+
+    /// Sets the URL of the full changelog.
+    ///
+    /// If this is set, a link to the section of the release in the full
+    /// changelog is appended to truncated notes. The link counts toward the
+    /// limits, and is omitted if it alone doesn't fit within them.
+    pub fn changelog_url(&mut self, url: &str) -> &mut Self {
+        self.changelog_url = Some(url.into());
+        self
+    }
+
+    /// Sets the text of the link to the full changelog.
+    ///
+    /// The default is `See the full changelog`.
+    pub fn link_text(&mut self, text: &str) -> &mut Self {
+        self.link_text = Some(text.into());
+        self
+    }
+
+    fn fits(&self, bytes: usize, lines: usize) -> bool {
+        self.max_bytes.is_none_or(|max| bytes <= max)
+            && self.max_lines.is_none_or(|max| lines <= max)
+    }
+}
+
+pub(crate) fn truncate<'a>(release: &Release<'a>, options: &TruncateOptions) -> Cow<'a, str> {
+    let notes = release.notes;
+    if options.fits(notes.len(), notes.lines().count()) {
+        return Cow::Borrowed(notes);
+    }
+    let link = options.changelog_url.as_deref().map(|url| {
+        let text = options.link_text.as_deref().unwrap_or(DEFAULT_LINK_TEXT);
+        let url = url.split('#').next().unwrap();
+        format!("[{text}]({url}#{})", release.anchor())
+    });
+    let link = link.filter(|link| options.fits(link.len(), 1));
+
+    // Finds the last block boundary at which the notes and the link fit
+    // within the limits.
+    let mut end = 0;
+    // If `Some`, we are in a code block (``` or ~~~).
+    let mut on_code_block: Option<&[u8]> = None;
+    // If `true`, we are in a comment (`<!--` and `-->`).
+    let mut on_comment = false;
+    // If `true`, we are in an inline comment (`... <!--`).
+    let mut is_inline_comment = false;
+    let mut prev_blank = true;
+    let mut pos = 0;
+    for line in notes.split_inclusive('\n') {
+        let trimmed = trim_start(line.trim_end());
+        let is_blank = trimmed.is_empty();
+        // Never cut inside a code block or a comment.
+        let was_outside = on_code_block.is_none() && !on_comment;
+        handle_non_heading_line(
+            &mut on_code_block,
+            &mut on_comment,
+            &mut is_inline_comment,
+            trimmed.as_bytes(),
+        );
+        let is_boundary = was_outside
+            && !is_blank
+            && (prev_blank
+                || plain::list_marker(trimmed).is_some()
+                || trimmed.starts_with('#')
+                // The opening fence of a code block.
+                || on_code_block.is_some());
+        if is_boundary && pos != 0 {
+            let kept = notes[..pos].trim_end();
+            let (bytes, lines) = match &link {
+                Some(link) => (kept.len() + 2 + link.len(), kept.lines().count() + 2),
+                None => (kept.len(), kept.lines().count()),
+            };
+            if !options.fits(bytes, lines) {
+                break;
+            }
+            end = kept.len();
+        }
+        prev_blank = is_blank;
+        pos += line.len();
+    }
+    let mut out = String::from(&notes[..end]);
+    if let Some(link) = link {
+        if !out.is_empty() {
+            out.push_str("\n\n");
+        }
+        out.push_str(&link);
+    }
+    Cow::Owned(out)
+}
@@ -3,7 +3,11 @@
 #![cfg(feature = "default")]
 #![cfg(not(miri))] // Miri doesn't support std::process::Command: https://github.com/rust-lang/miri/issues/3374
 
-use std::{ffi::OsStr, path::Path, process::Command};
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 use fs_err as fs;
 use indexmap::IndexMap;
@@ -21,6 +25,50 @@ fn parse_changelog<O: AsRef<OsStr>>(args: impl AsRef<[O]>) -> Command {
     cmd
 }
 
+/// Creates a new empty directory for the test, and returns its path.
+fn tmp_dir(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Creates a new git repository with the given files committed, and returns
+/// its path.
+fn git_repo(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = tmp_dir(name);
+    git(&dir, ["init", "-q"]);
+    git_commit(&dir, files, "initial commit");
+    dir
+}
+
+fn git_commit(dir: &Path, files: &[(&str, &str)], message: &str) {
+    for &(path, contents) in files {
+        fs::write(dir.join(path), contents).unwrap();
+    }
+    git(dir, ["add", "-A"]);
+    git(
+        dir,
+        [
+            "-c",
+            "user.name=test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            message,
+        ],
+    );
+}
+
+fn git<O: AsRef<OsStr>>(dir: &Path, args: impl AsRef<[O]>) {
+    Command::new("git").arg("-C").arg(dir).args(args.as_ref()).assert_success();
+}
+
 #[test]
 fn success() {
     parse_changelog(["tests/fixtures/pin-project.md"]).assert_success().stdout_eq(
@@ -98,8 +146,30 @@ fn failure() {
         "--title",
         "--title-no-link",
         "--json",
+        "--list",
+        "--columns=version",
+        "--reverse",
+        "--sort=semver",
+        "--format=html",
+        "--heading-offset=1",
+        "--top-heading-level=1",
+        "--anchors",
+        "--standalone",
+        "--width=80",
+        "--no-link-urls",
+        "--strip-comments",
+        "--base-url=https://example.com",
+        "--auto-link=https://example.com",
+        "--max-bytes=1",
+        "--max-lines=1",
+        "--full-changelog-url=https://example.com",
+        "--full-changelog-text=a",
         "--version-format=version",
         "--prefix-format=v",
+        "--package=a",
+        "--rev=HEAD",
+        "--workspace",
+        "--manifest-path=Cargo.toml",
     ] {
         parse_changelog(["tests/fixtures/pin-project.md", "0.0.0", flag, flag])
             .assert_failure()
@@ -146,6 +216,1387 @@ fn failure() {
         );
 }
 
+#[test]
+fn list() {
+    let changelog = "\
+# Changelog
+
+## [Unreleased]
+
+## [0.10.0] - 2020-03-01
+
+## 0.2.0 - 2020-02-01
+
+## 0.10.0-alpha.1
+
+## 0.1.0
+";
+    parse_changelog(["-", "--list"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_eq("Unreleased\n0.10.0\n0.2.0\n0.10.0-alpha.1\n0.1.0");
+    parse_changelog(["-", "--list", "--reverse"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_eq("0.1.0\n0.10.0-alpha.1\n0.2.0\n0.10.0\nUnreleased");
+    parse_changelog(["-", "--list", "--sort", "semver"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_eq("Unreleased\n0.10.0\n0.10.0-alpha.1\n0.2.0\n0.1.0");
+    parse_changelog(["-", "--list", "--sort", "semver", "--reverse"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_eq("0.1.0\n0.2.0\n0.10.0-alpha.1\n0.10.0\nUnreleased");
+    parse_changelog(["-", "--list", "--columns", "version,date,line,title"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_eq(
+            "Unreleased\t\t3\t[Unreleased]\n\
+             0.10.0\t2020-03-01\t5\t[0.10.0] - 2020-03-01\n\
+             0.2.0\t2020-02-01\t7\t0.2.0 - 2020-02-01\n\
+             0.10.0-alpha.1\t\t9\t0.10.0-alpha.1\n\
+             0.1.0\t\t11\t0.1.0",
+        );
+
+    parse_changelog(["tests/fixtures/pin-project.md", "--list", "--json"])
+        .assert_failure()
+        .stderr_contains("--list may not be used together with --json");
+    parse_changelog(["tests/fixtures/pin-project.md", "1.0.0", "--list"])
+        .assert_failure()
+        .stderr_contains("--list may not be used together with [VERSION]");
+    parse_changelog(["tests/fixtures/pin-project.md", "--sort", "semver"])
+        .assert_failure()
+        .stderr_contains("--sort can only be used together with --list");
+    parse_changelog(["tests/fixtures/pin-project.md", "--list", "--sort", "date"])
+        .assert_failure()
+        .stderr_contains("unknown sort order 'date'");
+    parse_changelog(["tests/fixtures/pin-project.md", "--list", "--columns", "version,notes"])
+        .assert_failure()
+        .stderr_contains("unknown column 'notes'");
+}
+
+#[test]
+fn tags() {
+    let changelog = "\
+## [Unreleased]
+
+## [0.3.0]
+
+## [0.2.0]
+
+## [0.1.0]
+";
+    let dir = git_repo("tags", &[("CHANGELOG.md", changelog)]);
+    for tag in ["v0.1.0", "v0.2.0", "v0.3.0", "very-old", "other-0.9.0"] {
+        git(&dir, ["tag", tag]);
+    }
+    parse_changelog(["tags", "CHANGELOG.md"]).current_dir(&dir).assert_success().stdout_eq("");
+
+    git(&dir, ["tag", "v0.4.0"]);
+    git(&dir, ["tag", "-d", "v0.2.0"]);
+    parse_changelog(["tags", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stdout_eq(
+            "v0.4.0: tag has no release note in changelog\n\
+             0.2.0: release has no tag (expected 'v0.2.0')",
+        )
+        .stderr_contains("found 2 mismatch(es) between CHANGELOG.md and git tags");
+
+    parse_changelog(["tags", "CHANGELOG.md", "--tag-prefix", "other-"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stdout_eq(
+            "other-0.9.0: tag has no release note in changelog\n\
+             0.3.0: release has no tag (expected 'other-0.3.0')\n\
+             0.2.0: release has no tag (expected 'other-0.2.0')\n\
+             0.1.0: release has no tag (expected 'other-0.1.0')",
+        );
+
+    let path = dir.join("CHANGELOG.md");
+    parse_changelog([OsStr::new("tags"), OsStr::new("--repo"), dir.as_os_str(), path.as_os_str()])
+        .assert_failure()
+        .stderr_contains("found 2 mismatch(es)");
+}
+
+#[test]
+fn rev() {
+    let dir = git_repo("rev", &[("CHANGELOG.md", "## [0.1.0] - 2020-01-01\n\nInitial release\n")]);
+    git(&dir, ["tag", "v0.1.0"]);
+    git_commit(
+        &dir,
+        &[(
+            "CHANGELOG.md",
+            "## 0.2.0\n\n- Bug fixes.\n\n## [0.1.0] - 2020-01-01\n\nFirst release\n",
+        )],
+        "release 0.2.0",
+    );
+
+    parse_changelog(["CHANGELOG.md"]).current_dir(&dir).assert_success().stdout_eq("- Bug fixes.");
+    parse_changelog(["--rev", "v0.1.0", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("Initial release");
+    parse_changelog(["git:HEAD~1:CHANGELOG.md", "0.1.0", "--title-no-link"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("0.1.0 - 2020-01-01");
+    parse_changelog(["git:HEAD:CHANGELOG.md", "0.1.0"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("First release");
+    let text = parse_changelog(["--rev", "HEAD~1", "--json", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout;
+    let changelog: ChangelogOwned = serde_json::from_str(&text).unwrap();
+    assert_eq!(changelog.len(), 1);
+    assert_eq!(changelog["0.1.0"].notes, "Initial release");
+
+    // Absolute paths are resolved from the root of the repository.
+    let path = dir.join("CHANGELOG.md");
+    parse_changelog(["--rev".as_ref(), "v0.1.0".as_ref(), path.as_os_str()])
+        .assert_success()
+        .stdout_eq("Initial release");
+    parse_changelog([format!("git:HEAD~1:{}", path.display()).as_str(), "0.1.0", "--title"])
+        .assert_success()
+        .stdout_eq("[0.1.0] - 2020-01-01");
+
+    parse_changelog(["--rev", "v9.9.9", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("`git show v9.9.9:./CHANGELOG.md` failed");
+    parse_changelog(["--rev", "HEAD", "-"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("--rev may not be used together with standard input");
+    parse_changelog(["--rev", "HEAD", "git:HEAD:CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("--rev may not be used together with 'git:<REV>:<PATH>' path");
+}
+
+#[test]
+fn package() {
+    let dir = tmp_dir("package");
+    let path = dir.join("CHANGELOG.md");
+    fs::write(
+        &path,
+        "## my-crate 0.2.0\n\n- Bug fixes.\n\n## my-crate-macros 0.2.0\n\n- Added `foo` macro.\n\n\
+         ## my-crate 0.1.0\n\nInitial release\n",
+    )
+    .unwrap();
+    let prefix = "--prefix-format=^(?<package>[0-9A-Za-z_-]+) ";
+
+    parse_changelog([path.as_os_str(), OsStr::new(prefix), OsStr::new("--package=my-crate")])
+        .assert_success()
+        .stdout_eq("- Bug fixes.");
+    parse_changelog([
+        path.as_os_str(),
+        OsStr::new("0.2.0"),
+        OsStr::new(prefix),
+        OsStr::new("--package=my-crate-macros"),
+    ])
+    .assert_success()
+    .stdout_eq("- Added `foo` macro.");
+    parse_changelog([
+        path.as_os_str(),
+        OsStr::new(prefix),
+        OsStr::new("--package=my-crate"),
+        OsStr::new("--list"),
+    ])
+    .assert_success()
+    .stdout_eq("0.2.0\n0.1.0");
+    let text = parse_changelog([
+        path.as_os_str(),
+        OsStr::new(prefix),
+        OsStr::new("--package=my-crate"),
+        OsStr::new("--json"),
+    ])
+    .assert_success()
+    .stdout;
+    let changelog: ChangelogOwned = serde_json::from_str(&text).unwrap();
+    assert_eq!(changelog.len(), 2);
+    assert_eq!(changelog["0.1.0"].notes, "Initial release");
+
+    parse_changelog([path.as_os_str(), OsStr::new(prefix), OsStr::new("--package=foo")])
+        .assert_failure()
+        .stderr_contains("no release note was found for package 'foo' in");
+    parse_changelog([path.as_os_str(), OsStr::new("--package=my-crate")])
+        .assert_failure()
+        .stderr_contains("--package can only be used together with --prefix-format");
+}
+
+#[test]
+fn workspace() {
+    let dir = tmp_dir("workspace");
+    let files = [
+        (
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/excluded\"]\n\n\
+             [workspace.package]\nversion = \"0.2.0\"\n\n\
+             [package]\nname = \"root\"\nversion = \"1.0.0\"\n",
+        ),
+        ("CHANGELOG.md", "## [Unreleased]\n\n## [1.0.0]\n\n- Stabilized.\n"),
+        ("crates/a/Cargo.toml", "[package]\nname = \"a\"\nversion.workspace = true\n"),
+        ("crates/a/CHANGELOG.md", "## 0.2.0\n\n- Added `foo`.\n\n## 0.1.0\n\nInitial release\n"),
+        ("crates/b/Cargo.toml", "[package]\nname = \"b\"\nversion = \"0.1.0\"\n"),
+        ("crates/b/CHANGELOG.md", "## 0.1.0\n\nInitial release\n"),
+        // No changelog.
+        ("crates/c/Cargo.toml", "[package]\nname = \"c\"\nversion = \"0.1.0\"\n"),
+        // No version (unpublished).
+        ("crates/d/Cargo.toml", "[package]\nname = \"d\"\n"),
+        ("crates/d/CHANGELOG.md", "## [Unreleased]\n"),
+        ("crates/excluded/Cargo.toml", "[package]\nname = \"excluded\"\nversion = \"0.1.0\"\n"),
+        ("crates/excluded/CHANGELOG.md", "## 0.0.1\n"),
+        // Not a package.
+        ("crates/docs/README.md", ""),
+    ];
+    for (path, contents) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    parse_changelog(["--workspace"]).current_dir(&dir).assert_success().stdout_eq(
+        "## root 1.0.0\n\n- Stabilized.\n\n## a 0.2.0\n\n- Added `foo`.\n\n## b 0.1.0\n\nInitial release",
+    );
+    let text = parse_changelog([
+        OsStr::new("--workspace"),
+        OsStr::new("--json"),
+        OsStr::new("--manifest-path"),
+        dir.join("Cargo.toml").as_os_str(),
+    ])
+    .assert_success()
+    .stdout;
+    let releases: Vec<WorkspaceRelease> = serde_json::from_str(&text).unwrap();
+    assert_eq!(releases.len(), 3);
+    assert_eq!(releases[1].name, "a");
+    assert_eq!(releases[1].version, "0.2.0");
+    assert_eq!(Path::new(&releases[1].path), dir.join("crates/a/CHANGELOG.md"));
+    assert_eq!(releases[1].title, "0.2.0");
+    assert_eq!(releases[1].notes, "- Added `foo`.");
+
+    fs::write(dir.join("crates/b/Cargo.toml"), "[package]\nname = \"b\"\nversion = \"0.1.1\"\n")
+        .unwrap();
+    fs::write(dir.join("crates/a/CHANGELOG.md"), "## 0.1.0\n\nInitial release\n").unwrap();
+    // Release notes of other packages are still output.
+    parse_changelog(["--workspace"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stdout_eq("## root 1.0.0\n\n- Stabilized.")
+        .stderr_contains("failed to get release notes for 2 package(s) in workspace `Cargo.toml`")
+        .stderr_contains("not found release note for '0.2.0' of 'a' in crates/a/CHANGELOG.md")
+        .stderr_contains("not found release note for '0.1.1' of 'b' in crates/b/CHANGELOG.md");
+    parse_changelog(["--workspace", "CHANGELOG.md"])
+        .assert_failure()
+        .stderr_contains("--workspace may not be used together with <PATH>");
+    parse_changelog(["--workspace", "--list"])
+        .assert_failure()
+        .stderr_contains("--workspace may not be used together with --list");
+    parse_changelog(["CHANGELOG.md", "--manifest-path", "Cargo.toml"])
+        .assert_failure()
+        .stderr_contains("--manifest-path can only be used together with --workspace");
+}
+
+#[test]
+fn check_version() {
+    let dir = tmp_dir("check-version");
+    let files = [
+        ("Cargo.toml", "[workspace]\n\n[workspace.package]\nversion = \"0.2.0\"\n"),
+        ("crate/Cargo.toml", "[package]\nname = \"a\"\nversion.workspace = true\n"),
+        ("crate/CHANGELOG.md", "## [Unreleased]\n\n## 0.2.0\n\n- Bug fixes.\n\n## 0.1.0\n"),
+        ("npm/package.json", r#"{ "name": "a", "version": "0.1.0" }"#),
+        ("npm/CHANGELOG.md", "## [Unreleased]\n\n## 0.2.0\n\n- Bug fixes.\n\n## 0.1.0\n"),
+        ("py/pyproject.toml", "[project]\nname = \"a\"\nversion = \"0.3.0\"\n"),
+        ("py/CHANGELOG.md", "## [Unreleased]\n\n- Added `foo`.\n\n## 0.2.0\n\n- Bug fixes.\n"),
+        ("version.txt", "VERSION = '0.2.0'\n"),
+    ];
+    for (path, contents) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    parse_changelog(["check-version", "crate/CHANGELOG.md"]).current_dir(&dir).assert_success();
+    parse_changelog(["check-version", "CHANGELOG.md"])
+        .current_dir(dir.join("crate"))
+        .assert_success();
+    parse_changelog(["check-version", "npm/CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains(
+            "release note for '0.1.0' (from `npm/package.json`) in npm/CHANGELOG.md is empty",
+        );
+    parse_changelog(["check-version", "py/CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains(
+            "not found release note for '0.3.0' (from `py/pyproject.toml`) in py/CHANGELOG.md",
+        );
+    parse_changelog(["check-version", "py/CHANGELOG.md", "--allow-unreleased"])
+        .current_dir(&dir)
+        .assert_success();
+    parse_changelog(["check-version", "npm/CHANGELOG.md", "--manifest", "crate/Cargo.toml"])
+        .current_dir(&dir)
+        .assert_success();
+    parse_changelog([
+        "check-version",
+        "py/CHANGELOG.md",
+        "--version-file",
+        "version.txt",
+        "--version-regex",
+        "VERSION = '(.*)'",
+    ])
+    .current_dir(&dir)
+    .assert_success();
+    parse_changelog([
+        "check-version",
+        "crate/CHANGELOG.md",
+        "--version-file",
+        "version.txt",
+        "--version-regex",
+        "^v(?<version>.*)",
+    ])
+    .current_dir(&dir)
+    .assert_failure()
+    .stderr_contains("--version-regex did not match `version.txt`");
+    parse_changelog(["check-version", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("no `package` table in `Cargo.toml`");
+    parse_changelog(["check-version", "crate/CHANGELOG.md", "--version-file", "version.txt"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("--version-file can only be used together with --version-regex");
+}
+
+#[test]
+fn cargo_changelog() {
+    let dir = tmp_dir("cargo-changelog");
+    let files = [
+        ("Cargo.toml", "[package]\nname = \"a\"\nversion = \"0.2.0\"\n"),
+        (
+            "CHANGELOG.md",
+            "## [Unreleased]\n\n## [0.2.0] - 2020-02-01\n\n- Bug fixes.\n\n## 0.1.0\n\nInitial release\n",
+        ),
+        ("src/lib.rs", ""),
+    ];
+    for (path, contents) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    let cargo_changelog = |args: &[&str]| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_cargo-changelog"));
+        cmd.current_dir(dir.join("src"));
+        cmd.args(args);
+        cmd
+    };
+
+    // Invoked by cargo as `cargo-changelog changelog [ARGS]...`.
+    cargo_changelog(&["changelog"]).assert_success().stdout_eq("- Bug fixes.");
+    cargo_changelog(&[]).assert_success().stdout_eq("- Bug fixes.");
+    cargo_changelog(&["changelog", "--title-no-link"])
+        .assert_success()
+        .stdout_eq("0.2.0 - 2020-02-01");
+    cargo_changelog(&["changelog", "0.1.0"]).assert_success().stdout_eq("Initial release");
+    cargo_changelog(&["changelog", "--all"]).assert_success().stdout_eq("Unreleased\n0.2.0\n0.1.0");
+    cargo_changelog(&["changelog", "--manifest-path", dir.join("Cargo.toml").to_str().unwrap()])
+        .assert_success()
+        .stdout_eq("- Bug fixes.");
+
+    // Works without parse-changelog binary in the same directory.
+    let bin_dir = dir.join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let exe = bin_dir.join(format!("cargo-changelog{}", std::env::consts::EXE_SUFFIX));
+    fs::copy(env!("CARGO_BIN_EXE_cargo-changelog"), &exe).unwrap();
+    Command::new(&exe)
+        .arg("changelog")
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("- Bug fixes.");
+
+    fs::write(dir.join("Cargo.toml"), "[package]\nname = \"a\"\nversion = \"0.3.0\"\n").unwrap();
+    cargo_changelog(&["changelog"])
+        .assert_failure()
+        .stderr_contains("not found release note for '0.3.0' in");
+    cargo_changelog(&["changelog", "--all", "0.1.0"])
+        .assert_failure()
+        .stderr_contains("--all may not be used together with [VERSION]");
+    fs::remove_file(dir.join("CHANGELOG.md")).unwrap();
+    cargo_changelog(&["changelog"])
+        .assert_failure()
+        .stderr_contains("no CHANGELOG.md was found next to");
+}
+
+#[test]
+fn deps() {
+    let dir = tmp_dir("deps");
+    let lockfile = |packages: &[(&str, &str)]| {
+        let packages: Vec<_> = packages
+            .iter()
+            .map(|(name, version)| {
+                format!(
+                    "\n[[package]]\nname = \"{name}\"\nversion = \"{version}\"\n\
+                     source = \"registry+https://github.com/rust-lang/crates.io-index\"\n"
+                )
+            })
+            .collect();
+        format!("version = 4\n{}", packages.concat())
+    };
+    let registry = "cargo-home/registry/src/index.crates.io-1949cf8c6b5b557f";
+    let files = [
+        ("old.lock", lockfile(&[("a", "0.1.0"), ("b", "1.0.0"), ("c", "0.1.0"), ("d", "0.1.0")])),
+        (
+            "new.lock",
+            lockfile(&[("a", "0.3.0"), ("b", "1.0.0"), ("c", "0.2.0"), ("d", "0.2.0"), ("e", "0.1.0")])
+                + "\n[[package]]\nname = \"local\"\nversion = \"0.1.0\"\n",
+        ),
+        (
+            &format!("{registry}/a-0.3.0/CHANGELOG.md"),
+            "## [Unreleased]\n\n## [0.3.0] - 2020-03-01\n\n- Added `foo`.\n\n\
+             ## [0.2.0] - 2020-02-01\n\n- Bug fixes.\n\n## [0.1.0] - 2020-01-01\n\nInitial release\n"
+                .to_owned(),
+        ),
+        (&format!("{registry}/c-0.2.0/README.md"), String::new()),
+    ];
+    for (path, contents) in &files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    parse_changelog(["deps", "old.lock", "new.lock"])
+        .current_dir(&dir)
+        .env("CARGO_HOME", dir.join("cargo-home"))
+        .assert_success()
+        .stdout_eq(format!(
+            "## a 0.1.0 -> 0.3.0\n\n\
+             ### [0.3.0] - 2020-03-01\n\n- Added `foo`.\n\n\
+             ### [0.2.0] - 2020-02-01\n\n- Bug fixes.\n\n\
+             ## c 0.1.0 -> 0.2.0\n\n\
+             No changelog was found in `{}`.\n\n\
+             ## d 0.1.0 -> 0.2.0\n\n\
+             Sources were not found in `{}`.",
+            dir.join(registry).join("c-0.2.0").display(),
+            dir.join("cargo-home/registry/src").display(),
+        ));
+    parse_changelog(["deps", "old.lock"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("two Cargo.lock paths must be specified");
+}
+
+#[test]
+fn format_html() {
+    parse_changelog(["tests/fixtures/pin-project.md", "0.4.0-alpha.6", "--format", "html"])
+        .assert_success()
+        .stdout_eq(
+            "<h2>0.4.0-alpha.6 - 2019-09-01</h2>\n<ul>\n\
+             <li><a href=\"https://github.com/taiki-e/pin-project/pull/53\">Allow using \
+             <code>#[pin_project]</code> type with private field types</a></li>\n</ul>",
+        );
+    parse_changelog([
+        "tests/fixtures/pin-project.md",
+        "0.4.0-alpha.6",
+        "--format=html",
+        "--heading-offset=1",
+        "--anchors",
+        "--standalone",
+    ])
+    .assert_success()
+    .stdout_contains("<h3 id=\"040-alpha6---2019-09-01\">0.4.0-alpha.6 - 2019-09-01</h3>")
+    .stdout_contains("<title>0.4.0-alpha.6 - 2019-09-01</title>");
+
+    parse_changelog(["tests/fixtures/pin-project.md", "--format=html", "--json"])
+        .assert_failure()
+        .stderr_contains("--format html may not be used together with --json");
+    parse_changelog(["tests/fixtures/pin-project.md", "--anchors"])
+        .assert_failure()
+        .stderr_contains("--anchors can only be used together with --format html");
+    parse_changelog(["tests/fixtures/pin-project.md", "--format=xml"])
+        .assert_failure()
+        .stderr_contains("unknown output format 'xml' (expected markdown, html, or text)");
+}
+
+#[test]
+fn format_text() {
+    parse_changelog(["tests/fixtures/pin-project.md", "0.4.0-alpha.6", "--format", "text"])
+        .assert_success()
+        .stdout_eq(
+            "- Allow using #[pin_project] type with private field types \
+             (https://github.com/taiki-e/pin-project/pull/53)",
+        );
+    parse_changelog([
+        "tests/fixtures/pin-project.md",
+        "0.4.0-alpha.6",
+        "--format=text",
+        "--width=30",
+        "--no-link-urls",
+    ])
+    .assert_success()
+    .stdout_eq("- Allow using #[pin_project]\n  type with private field\n  types");
+
+    parse_changelog(["tests/fixtures/pin-project.md", "--format=text", "--list"])
+        .assert_failure()
+        .stderr_contains("--format text may not be used together with --list");
+    parse_changelog(["tests/fixtures/pin-project.md", "--width=80"])
+        .assert_failure()
+        .stderr_contains("--width can only be used together with --format text");
+    parse_changelog(["tests/fixtures/pin-project.md", "--format=html", "--no-link-urls"])
+        .assert_failure()
+        .stderr_contains("--no-link-urls can only be used together with --format text");
+}
+
+#[test]
+fn base_url() {
+    let dir = git_repo(
+        "base_url",
+        &[("CHANGELOG.md", "## [0.1.0](../../releases/tag/v0.1.0)\n\n- See [docs](docs/a.md).\n")],
+    );
+    let base_url = "--base-url=https://github.com/owner/repo/blob/{rev}/CHANGELOG.md";
+    parse_changelog(["CHANGELOG.md", base_url])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("- See [docs](https://github.com/owner/repo/blob/HEAD/docs/a.md).");
+    parse_changelog(["CHANGELOG.md", base_url, "--rev=HEAD~0", "--title"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("[0.1.0](https://github.com/owner/repo/releases/tag/v0.1.0)");
+    parse_changelog(["git:HEAD~0:CHANGELOG.md", base_url, "--format=text"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("- See docs (https://github.com/owner/repo/blob/HEAD~0/docs/a.md).");
+
+    parse_changelog(["CHANGELOG.md", base_url, "--json"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("--base-url may not be used together with --json");
+}
+
+#[test]
+fn auto_link() {
+    let changelog = "## 0.1.0 (#1)\n\n- Fix crash ([#2](../../pull/2), GH-3, thanks @user)\n";
+    parse_changelog(["-", "--auto-link=https://github.com/owner/repo"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_eq(
+            "- Fix crash ([#2](../../pull/2), [GH-3](https://github.com/owner/repo/issues/3), \
+             thanks [@user](https://github.com/user))",
+        );
+    parse_changelog(["-", "--auto-link=https://github.com/owner/repo", "--title"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_eq("0.1.0 ([#1](https://github.com/owner/repo/issues/1))");
+    parse_changelog([
+        "-",
+        "--auto-link=https://gitlab.com/group/subgroup/repo",
+        "--issue-url={repository}/-/issues/{number}",
+        "--title",
+    ])
+    .spawn_with_stdin(changelog)
+    .assert_success()
+    .stdout_eq("0.1.0 ([#1](https://gitlab.com/group/subgroup/repo/-/issues/1))");
+
+    parse_changelog(["-", "--issue-url={repository}/-/issues/{number}"])
+        .spawn_with_stdin(changelog)
+        .assert_failure()
+        .stderr_contains("--issue-url can only be used together with --auto-link");
+    parse_changelog(["-", "--auto-link=https://github.com/owner/repo", "--list"])
+        .spawn_with_stdin(changelog)
+        .assert_failure()
+        .stderr_contains("--auto-link may not be used together with --list");
+}
+
+#[test]
+fn heading_offset() {
+    let changelog = "## 0.1.0\n\n### Added\n\n- Foo\n\n#### Fixed\n\n```\n### Bar\n```\n";
+    parse_changelog(["-", "--heading-offset=1"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_eq("#### Added\n\n- Foo\n\n##### Fixed\n\n```\n### Bar\n```");
+    parse_changelog(["-", "--heading-offset=-1"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_eq("## Added\n\n- Foo\n\n### Fixed\n\n```\n### Bar\n```");
+    parse_changelog(["-", "--top-heading-level=2"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_eq("## Added\n\n- Foo\n\n### Fixed\n\n```\n### Bar\n```");
+
+    parse_changelog(["-", "--heading-offset=1", "--top-heading-level=1"])
+        .spawn_with_stdin(changelog)
+        .assert_failure()
+        .stderr_contains("--heading-offset may not be used together with --top-heading-level");
+    parse_changelog(["-", "--heading-offset=1", "--title"])
+        .spawn_with_stdin(changelog)
+        .assert_failure()
+        .stderr_contains("--heading-offset may not be used together with --title");
+    parse_changelog(["-", "--top-heading-level=7"])
+        .spawn_with_stdin(changelog)
+        .assert_failure()
+        .stderr_contains("--top-heading-level must be between 1 and 6");
+    parse_changelog(["-", "--heading-offset=-1", "--format=html"])
+        .spawn_with_stdin(changelog)
+        .assert_failure()
+        .stderr_contains("negative --heading-offset may not be used together with --format html");
+}
+
+#[test]
+fn strip_comments() {
+    let changelog = "## 0.1.0\n\n<!-- TODO:\nadd migration guide -->\n\n- Foo <!-- #12 -->\n\n```\n<!-- code -->\n```\n";
+    parse_changelog(["-", "--strip-comments"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_eq("- Foo\n\n```\n<!-- code -->\n```");
+    parse_changelog(["-", "--strip-comments", "--json"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_contains(r#""notes":"- Foo\n\n```\n<!-- code -->\n```""#);
+    parse_changelog(["-", "--strip-comments", "--format=text"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_eq("- Foo\n\n<!-- code -->");
+    parse_changelog(["-", "--strip-comments"])
+        .spawn_with_stdin("## 0.1.0\n\n- a `<!-- x -->` b\n")
+        .assert_success()
+        .stdout_eq("- a `<!-- x -->` b");
+
+    parse_changelog(["-", "--strip-comments", "--list"])
+        .spawn_with_stdin(changelog)
+        .assert_failure()
+        .stderr_contains("--strip-comments may not be used together with --list");
+}
+
+#[test]
+fn truncate() {
+    let changelog = "## [0.1.0] - 2024-01-01\n\n### Added\n\n- Foo\n- Bar\n\n```\ncode\n```\n";
+    parse_changelog(["-", "--max-lines=5"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_eq("### Added\n\n- Foo\n- Bar");
+    parse_changelog([
+        "-",
+        "--max-lines=6",
+        "--full-changelog-url=https://example.com/CHANGELOG.md",
+        "--full-changelog-text=Full changelog",
+    ])
+    .spawn_with_stdin(changelog)
+    .assert_success()
+    .stdout_eq(
+        "### Added\n\n- Foo\n- Bar\n\n[Full changelog](https://example.com/CHANGELOG.md#010---2024-01-01)",
+    );
+    parse_changelog(["-", "--max-bytes=1000"])
+        .spawn_with_stdin(changelog)
+        .assert_success()
+        .stdout_eq("### Added\n\n- Foo\n- Bar\n\n```\ncode\n```");
+
+    parse_changelog(["-", "--full-changelog-url=https://example.com"])
+        .spawn_with_stdin(changelog)
+        .assert_failure()
+        .stderr_contains(
+            "--full-changelog-url can only be used together with --max-bytes or --max-lines",
+        );
+    parse_changelog(["-", "--max-lines=1", "--format=html"])
+        .spawn_with_stdin(changelog)
+        .assert_failure()
+        .stderr_contains("--format html may not be used together with --max-lines");
+}
+
+#[test]
+fn compare_links() {
+    let dir = tmp_dir("compare-links");
+    let changelog = "\
+# Changelog
+
+## [Unreleased]
+
+## [0.3.0] - 2020-03-01
+
+## [0.2.0] - 2020-02-01
+
+```
+[0.2.0]: https://example.com
+```
+
+## [0.1.0] - 2020-01-01
+
+[Unreleased]: https://github.com/owner/repo/compare/v0.2.0...HEAD
+[0.2.0]: https://github.com/owner/repo/compare/v0.1.0...v0.2.0
+[other]: https://example.com";
+    fs::write(dir.join("CHANGELOG.md"), changelog).unwrap();
+    let compare_url = "--compare-url=https://github.com/owner/repo/compare/{previous}...{current}";
+    let first_url = "--first-url=https://github.com/owner/repo/releases/tag/{current}";
+
+    parse_changelog(["compare-links", "CHANGELOG.md", compare_url])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq(
+            "[Unreleased]: https://github.com/owner/repo/compare/v0.3.0...HEAD\n\
+             [0.3.0]: https://github.com/owner/repo/compare/v0.2.0...v0.3.0\n\
+             [0.2.0]: https://github.com/owner/repo/compare/v0.1.0...v0.2.0",
+        );
+    parse_changelog(["compare-links", "CHANGELOG.md", compare_url, first_url, "--check"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stdout_eq(
+            "Unreleased: stale link reference definition (expected \
+             'https://github.com/owner/repo/compare/v0.3.0...HEAD', found \
+             'https://github.com/owner/repo/compare/v0.2.0...HEAD')\n\
+             0.3.0: missing link reference definition (expected \
+             '[0.3.0]: https://github.com/owner/repo/compare/v0.2.0...v0.3.0')\n\
+             0.1.0: missing link reference definition (expected \
+             '[0.1.0]: https://github.com/owner/repo/releases/tag/v0.1.0')",
+        )
+        .stderr_contains("found 3 mismatch(es) in link reference definitions of CHANGELOG.md");
+    assert_eq!(fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(), changelog);
+
+    parse_changelog(["compare-links", "CHANGELOG.md", compare_url, first_url, "--write"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("");
+    assert_eq!(
+        fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(),
+        changelog
+            .replace("v0.2.0...HEAD", "v0.3.0...HEAD")
+            .replace(
+                "[0.2.0]: https://github.com/owner/repo/compare/v0.1.0",
+                "[0.3.0]: https://github.com/owner/repo/compare/v0.2.0...v0.3.0\n\
+                 [0.2.0]: https://github.com/owner/repo/compare/v0.1.0"
+            )
+            .replace(
+                "v0.2.0\n[other]",
+                "v0.2.0\n[0.1.0]: https://github.com/owner/repo/releases/tag/v0.1.0\n[other]"
+            )
+    );
+    parse_changelog(["compare-links", "CHANGELOG.md", compare_url, first_url, "--check"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("");
+
+    // Definitions are appended at the end if there are none.
+    fs::write(dir.join("CHANGELOG.md"), "## [0.2.0]\n\n## [0.1.0]\n").unwrap();
+    parse_changelog(["compare-links", "CHANGELOG.md", compare_url, "--write"])
+        .current_dir(&dir)
+        .assert_success();
+    assert_eq!(
+        fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(),
+        "## [0.2.0]\n\n## [0.1.0]\n\n\
+         [0.2.0]: https://github.com/owner/repo/compare/v0.1.0...v0.2.0\n"
+    );
+
+    // Definitions are matched by the labels used in the release titles.
+    let changelog = "\
+## [v0.2.0] - 2020-02-01
+
+````
+```
+[v0.2.0]: https://example.com
+````
+
+## [v0.1.0] - 2020-01-01
+
+[v0.1.0]: https://github.com/owner/repo/releases/tag/v0.1.0
+";
+    fs::write(dir.join("CHANGELOG.md"), changelog).unwrap();
+    parse_changelog(["compare-links", "CHANGELOG.md", compare_url, first_url, "--write"])
+        .current_dir(&dir)
+        .assert_success();
+    assert_eq!(
+        fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(),
+        changelog.replace(
+            "[v0.1.0]: ",
+            "[v0.2.0]: https://github.com/owner/repo/compare/v0.1.0...v0.2.0\n[v0.1.0]: "
+        )
+    );
+    parse_changelog(["compare-links", "CHANGELOG.md", compare_url, first_url, "--check"])
+        .current_dir(&dir)
+        .assert_success();
+
+    // Titles that are not links have no definitions.
+    fs::write(dir.join("CHANGELOG.md"), "## 0.3.0\n\n## [0.2.0]\n\n## [0.1.0]\n").unwrap();
+    parse_changelog(["compare-links", "CHANGELOG.md", compare_url])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("[0.2.0]: https://github.com/owner/repo/compare/v0.1.0...v0.2.0");
+    // Definitions are not appended inside an unclosed code block or comment.
+    for end in ["```\n[0.0.1]: https://example.com\n", "<!--\n"] {
+        let changelog = format!("## [0.2.0]\n\n## [0.1.0]\n\n{end}");
+        fs::write(dir.join("CHANGELOG.md"), &changelog).unwrap();
+        parse_changelog(["compare-links", "CHANGELOG.md", compare_url, "--write"])
+            .current_dir(&dir)
+            .assert_failure()
+            .stderr_contains(
+                "cannot add link reference definitions at the end of CHANGELOG.md because it \
+                 ends inside a code block or a comment",
+            );
+        assert_eq!(fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(), changelog);
+    }
+
+    parse_changelog(["compare-links", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("no --compare-url specified");
+    parse_changelog(["compare-links", "CHANGELOG.md", compare_url, "--write", "--check"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("--write may not be used together with --check");
+}
+
+#[test]
+fn path_named_like_subcommand() {
+    let dir = tmp_dir("path-named-like-subcommand");
+    for name in ["diff", "fmt", "tags"] {
+        fs::write(dir.join(name), "## 0.1.0\n\n- Foo\n").unwrap();
+        parse_changelog([name]).current_dir(&dir).assert_success().stdout_eq("- Foo");
+    }
+    parse_changelog(["changelog"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("failed to read from file `changelog`");
+}
+
+#[test]
+fn fmt() {
+    let dir = tmp_dir("fmt");
+    let changelog = "\
+# Changelog
+## [Unreleased]
+### Fixed
+* Foo
+### Added
+* Bar
+## [0.1.0] - 2020-01-01
+- Baz
+";
+    let formatted = "\
+# Changelog
+
+## [Unreleased]
+
+### Added
+
+- Bar
+
+### Fixed
+
+- Foo
+
+## [0.1.0] - 2020-01-01
+
+- Baz
+";
+    fs::write(dir.join("CHANGELOG.md"), changelog).unwrap();
+
+    parse_changelog(["fmt", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq(formatted);
+    parse_changelog(["fmt", "CHANGELOG.md", "--heading-style", "setext", "--bullet", "*"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_contains(
+            "Changelog\n=========\n\n[Unreleased]\n------------\n\n### Added\n\n* Bar",
+        );
+    parse_changelog(["fmt", "CHANGELOG.md", "--check"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stdout_contains("--- CHANGELOG.md\n+++ CHANGELOG.md (formatted)\n@@")
+        .stdout_contains("-### Fixed\n-* Foo\n")
+        .stderr_contains("CHANGELOG.md is not formatted");
+    assert_eq!(fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(), changelog);
+
+    parse_changelog(["fmt", "CHANGELOG.md", "--write"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("");
+    assert_eq!(fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(), formatted);
+    parse_changelog(["fmt", "CHANGELOG.md", "--check"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("");
+
+    parse_changelog(["fmt", "CHANGELOG.md", "--heading-style", "foo"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("invalid heading style 'foo' (expected 'atx' or 'setext')");
+    parse_changelog(["fmt", "CHANGELOG.md", "--bullet", "--"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("invalid bullet '--' (expected '-', '*', or '+')");
+    parse_changelog(["fmt", "CHANGELOG.md", "--write", "--check"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("--write may not be used together with --check");
+
+    // Only convert headings.
+    let setext = "Changelog\n=========\n\n[0.1.0]\n-------\n* Foo\n";
+    fs::write(dir.join("CHANGELOG.md"), setext).unwrap();
+    parse_changelog(["fmt", "CHANGELOG.md", "--convert-headings", "atx"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("# Changelog\n\n## [0.1.0]\n* Foo");
+    parse_changelog(["fmt", "CHANGELOG.md", "--convert-headings", "atx", "--releases-only"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("Changelog\n=========\n\n## [0.1.0]\n* Foo");
+    parse_changelog(["fmt", "CHANGELOG.md", "--convert-headings", "setext", "--check"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("");
+    parse_changelog(["fmt", "CHANGELOG.md", "--convert-headings", "atx", "--write"])
+        .current_dir(&dir)
+        .assert_success();
+    assert_eq!(
+        fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(),
+        "# Changelog\n\n## [0.1.0]\n* Foo\n"
+    );
+    parse_changelog(["fmt", "CHANGELOG.md", "--releases-only"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("--releases-only can only be used together with --convert-headings");
+    parse_changelog(["fmt", "CHANGELOG.md", "--convert-headings", "atx", "--bullet", "*"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("--convert-headings may not be used together with --bullet");
+}
+
+#[test]
+fn diff() {
+    let dir = git_repo(
+        "diff",
+        &[(
+            "CHANGELOG.md",
+            "## [Unreleased]\n\n## 0.2.0\n\n- Bug fixes.\n\n## 0.1.0\n\nInitial release\n\n## 0.0.1\n",
+        )],
+    );
+    git_commit(
+        &dir,
+        &[(
+            "CHANGELOG.md",
+            "## [Unreleased]\n\n- Foo.\n\n## 0.3.0\n\n- Bar.\n\n## 0.2.0\n\n- Bug fixes.\n\n## 0.1.0 - 2020-01-01\n\nFirst release\n",
+        )],
+        "update changelog",
+    );
+
+    parse_changelog(["diff", "git:HEAD~1:CHANGELOG.md", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq(
+            "modified Unreleased (notes)\n\
+             \x20 + - Foo.\n\
+             added 0.3.0\n\
+             modified 0.1.0 (title and notes) [released version]\n\
+             \x20 title: 0.1.0 -> 0.1.0 - 2020-01-01\n\
+             \x20 - Initial release\n\
+             \x20 + First release\n\
+             removed 0.0.1",
+        );
+    parse_changelog(["diff", "CHANGELOG.md", "git:HEAD:CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("");
+
+    let text = parse_changelog(["diff", "--json", "git:HEAD~1:CHANGELOG.md", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout;
+    let changes: Vec<serde_json::Value> = serde_json::from_str(&text).unwrap();
+    assert_eq!(changes.len(), 4);
+    assert_eq!(
+        changes[2],
+        serde_json::json!({
+            "version": "0.1.0",
+            "change": "modified",
+            "released": true,
+            "title_changed": true,
+            "notes_changed": true,
+            "added_entries": ["First release"],
+            "removed_entries": ["Initial release"],
+        })
+    );
+
+    parse_changelog(["diff", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("two changelog paths must be specified");
+    parse_changelog(["diff", "-", "-"])
+        .assert_failure()
+        .stderr_contains("standard input may not be used for both <OLD> and <NEW>");
+}
+
+#[test]
+fn diff_check() {
+    let old = "\
+## [Unreleased]
+
+## 0.2.0
+
+- Foo.
+
+## 0.1.0
+
+- l1
+- l2
+- l3
+- l4
+- l5
+- l6
+- l7
+- l8
+- l9
+";
+    let dir = git_repo("diff_check", &[("CHANGELOG.md", old)]);
+    git_commit(
+        &dir,
+        &[("CHANGELOG.md", &old.replace("## [Unreleased]\n", "## [Unreleased]\n\n- Bar.\n"))],
+        "add unreleased changes",
+    );
+    parse_changelog(["diff", "--check", "git:HEAD~1:CHANGELOG.md", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("");
+    // A heading with the same title as a released version does not modify it.
+    git_commit(
+        &dir,
+        &[("CHANGELOG.md", &old.replace("## [Unreleased]\n", "## [Unreleased]\n\n### 0.2.0\n"))],
+        "add sub-heading",
+    );
+    parse_changelog(["diff", "git:HEAD~1:CHANGELOG.md", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("modified Unreleased (notes)\n  - - Bar.\n  + ### 0.2.0");
+    parse_changelog(["diff", "--check", "git:HEAD~1:CHANGELOG.md", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("");
+
+    git_commit(
+        &dir,
+        &[(
+            "CHANGELOG.md",
+            &old.replace("## 0.2.0\n", "## 0.2.0 - 2020-01-01\n")
+                .replace("- l2\n", "- L2\n")
+                .replace("- l5\n", "- l5\n  - l5.1\n")
+                .replace("- l9\n", "- l9\n- l10\n"),
+        )],
+        "edit released versions",
+    );
+    parse_changelog(["diff", "--check", "git:HEAD~1:CHANGELOG.md", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stdout_eq(
+            [
+                "modified 0.2.0",
+                "  title: 0.2.0 -> 0.2.0 - 2020-01-01",
+                "modified 0.1.0",
+                "  - - l2",
+                "  - - l5",
+                "  + - L2",
+                "  + - l5",
+                "      - l5.1",
+                "  + - l10",
+            ]
+            .join("\n"),
+        )
+        .stderr_contains("release notes of released version(s) were modified: 0.2.0, 0.1.0");
+
+    parse_changelog(["diff", "--check", "--json", "CHANGELOG.md", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("--json may not be used together with --check");
+}
+
+#[test]
+fn generate() {
+    let changelog = "\
+# Changelog
+
+## [Unreleased]
+
+### Fixed
+
+- Fix foo.
+
+## [0.1.0]
+
+Initial release
+";
+    let dir = git_repo("generate", &[("CHANGELOG.md", changelog)]);
+    git_commit(&dir, &[], "feat: add old feature");
+    git(&dir, ["tag", "v0.1.0"]);
+    git_commit(&dir, &[], "feat(parser): add bar");
+    git_commit(&dir, &[], "fix: fix baz\n\nDetails.");
+    git_commit(&dir, &[], "chore: update dependencies");
+    git_commit(&dir, &[], "refactor!: remove qux");
+    git_commit(&dir, &[], "perf: improve performance\n\nBREAKING CHANGE: requires Rust 1.85");
+    git_commit(&dir, &[], "not a conventional commit");
+
+    parse_changelog(["generate", "CHANGELOG.md"]).current_dir(&dir).assert_success().stdout_eq(
+        "\
+### Added
+
+- parser: add bar
+
+### Changed
+
+- **Breaking:** remove qux
+- **Breaking:** improve performance
+
+### Fixed
+
+- fix baz",
+    );
+    parse_changelog(["generate", "--since", "HEAD~2", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("### Changed\n\n- **Breaking:** improve performance");
+    // Commits are read from the repository that contains the changelog.
+    parse_changelog(["generate", "--since", "HEAD~2", dir.join("CHANGELOG.md").to_str().unwrap()])
+        .current_dir(tmp_dir("generate-outside"))
+        .assert_success()
+        .stdout_eq("### Changed\n\n- **Breaking:** improve performance");
+
+    parse_changelog(["generate", "--write", "CHANGELOG.md"]).current_dir(&dir).assert_success();
+    assert_eq!(
+        fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(),
+        "\
+# Changelog
+
+## [Unreleased]
+
+### Fixed
+
+- Fix foo.
+- fix baz
+
+### Added
+
+- parser: add bar
+
+### Changed
+
+- **Breaking:** remove qux
+- **Breaking:** improve performance
+
+## [0.1.0]
+
+Initial release
+"
+    );
+
+    fs::write(dir.join("CHANGELOG.md"), "## 0.1.0\n").unwrap();
+    parse_changelog(["generate", "--write", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("no 'Unreleased' section was found in changelog");
+    fs::write(dir.join("CHANGELOG.md"), "Unreleased\n==========\n0.1.0\n=====\n").unwrap();
+    parse_changelog(["generate", "--write", "--since", "HEAD~2", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_success();
+    assert_eq!(
+        fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(),
+        "Unreleased\n==========\n\n## Changed\n\n- **Breaking:** improve performance\n\n0.1.0\n=====\n"
+    );
+    // Entries are inserted after the last list item (including its continuation
+    // lines), and no blank line is added at the end of the changelog.
+    fs::write(
+        dir.join("CHANGELOG.md"),
+        "## [Unreleased]\n\n### Changed\n\n- Fix a thing (\n  #123)\n",
+    )
+    .unwrap();
+    parse_changelog(["generate", "--write", "--since", "HEAD~2", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_success();
+    assert_eq!(
+        fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(),
+        "## [Unreleased]\n\n### Changed\n\n- Fix a thing (\n  #123)\n- **Breaking:** improve performance\n"
+    );
+    // Headings in code blocks are ignored.
+    fs::write(dir.join("CHANGELOG.md"), "## [Unreleased]\n\n````\n```\n### Changed\n````\n")
+        .unwrap();
+    parse_changelog(["generate", "--write", "--since", "HEAD~2", "CHANGELOG.md"])
+        .current_dir(&dir)
+        .assert_success();
+    assert_eq!(
+        fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(),
+        "## [Unreleased]\n\n````\n```\n### Changed\n````\n\n### Changed\n\n- **Breaking:** improve performance\n"
+    );
+}
+
+#[test]
+fn assemble() {
+    let dir = tmp_dir("assemble");
+    let changelog = "\
+# Changelog
+
+## [Unreleased]
+
+## [0.1.0] - 2020-01-01
+
+Initial release
+";
+    fs::write(dir.join("CHANGELOG.md"), changelog).unwrap();
+    fs::create_dir(dir.join("changelog.d")).unwrap();
+    for (name, contents) in [
+        ("12.fixed.md", "Fix foo.\n"),
+        ("12.fixed.1.md", "Fix foo again.\n"),
+        ("3.fixed.md", "Fix bar.\n\nDetails.\n"),
+        ("45.added.md", "Add baz.\n"),
+        ("46.Security.md", "Fix CVE-XXXX-XXXX.\n"),
+        ("README.md", "Put news fragments here.\n"),
+        ("template.jinja", "{{ text }}\n"),
+        (".gitignore", "!.gitignore\n"),
+    ] {
+        fs::write(dir.join("changelog.d").join(name), contents).unwrap();
+    }
+
+    let section = "\
+## 0.2.0 - 2020-02-01
+
+### Added
+
+- Add baz.
+
+### Fixed
+
+- Fix bar.
+
+  Details.
+- Fix foo.
+- Fix foo again.
+
+### Security
+
+- Fix CVE-XXXX-XXXX.";
+    parse_changelog(["assemble", "CHANGELOG.md", "0.2.0", "--date", "2020-02-01"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq(section);
+    assert_eq!(fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(), changelog);
+
+    parse_changelog(["assemble", "CHANGELOG.md", "0.2.0", "--date", "2020-02-01", "--write"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("");
+    assert_eq!(
+        fs::read_to_string(dir.join("CHANGELOG.md")).unwrap(),
+        changelog.replace("## [0.1.0]", &format!("{section}\n\n## [0.1.0]"))
+    );
+    assert!(dir.join("changelog.d/12.fixed.md").exists());
+
+    parse_changelog(["assemble", "CHANGELOG.md", "0.2.0", "--write"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("release note for '0.2.0' already exists in changelog");
+    parse_changelog(["assemble", "CHANGELOG.md", "0.3.0", "--write", "--delete"])
+        .current_dir(&dir)
+        .assert_success();
+    assert!(
+        fs::read_to_string(dir.join("CHANGELOG.md")).unwrap().contains("## 0.3.0\n\n### Added")
+    );
+    assert!(!dir.join("changelog.d/12.fixed.md").exists());
+    assert!(!dir.join("changelog.d/12.fixed.1.md").exists());
+    assert!(dir.join("changelog.d/README.md").exists());
+    assert!(dir.join("changelog.d/template.jinja").exists());
+    assert!(dir.join("changelog.d/.gitignore").exists());
+    parse_changelog(["assemble", "CHANGELOG.md", "0.4.0"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("no news fragments were found in `changelog.d`");
+
+    fs::write(dir.join("changelog.d/1.fix.md"), "Fix foo.\n").unwrap();
+    fs::write(dir.join("changelog.d/2.fixed.md"), "Fix bar.\n").unwrap();
+    parse_changelog(["assemble", "CHANGELOG.md", "0.4.0"])
+        .current_dir(&dir)
+        .assert_success()
+        .stdout_eq("## 0.4.0\n\n### Fixed\n\n- Fix bar.")
+        .stderr_contains("1.fix.md` with unknown fragment type 'fix'");
+    parse_changelog(["assemble", "CHANGELOG.md", "0.4.0", "--delete"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("--delete can only be used together with --write");
+}
+
+#[test]
+fn changeset() {
+    let dir = tmp_dir("changeset");
+    fs::create_dir_all(dir.join("packages/a")).unwrap();
+    fs::create_dir_all(dir.join("packages/b")).unwrap();
+    fs::create_dir(dir.join(".changeset")).unwrap();
+    fs::write(dir.join("packages/a/CHANGELOG.md"), "# a\n\n## 1.2.3\n\n- Foo.\n").unwrap();
+    fs::write(dir.join("packages/b/CHANGELOG.md"), "# b\n\n## 0.2.0-rc.1\n\n- Bar.\n").unwrap();
+    fs::write(dir.join("package.json"), r#"{ "private": true, "workspaces": ["packages/*"] }"#)
+        .unwrap();
+    fs::write(dir.join("packages/a/package.json"), r#"{ "name": "a" }"#).unwrap();
+    fs::write(dir.join("packages/b/package.json"), r#"{ "name": "b" }"#).unwrap();
+    for (name, contents) in [
+        ("brave-cats-run.md", "---\n\"a\": patch\n\"b\": minor\n---\n\nFix a and add b.\n"),
+        ("quiet-dogs-sleep.md", "---\na: minor\n---\n\nAdd a.\n"),
+        ("README.md", "# Changesets\n"),
+        ("config.json", "{}\n"),
+    ] {
+        fs::write(dir.join(".changeset").join(name), contents).unwrap();
+    }
+
+    // The changelog of 'b' defaults to the one next to its package.json.
+    let args = ["changeset", "--changelog", "a=packages/a/CHANGELOG.md", "--date", "2020-01-01"];
+    parse_changelog(args).current_dir(&dir).assert_success().stdout_eq(
+        "\
+a: 1.2.3 -> 1.3.0 (packages/a/CHANGELOG.md)
+
+## 1.3.0 - 2020-01-01
+
+### Minor Changes
+
+- Add a.
+
+### Patch Changes
+
+- Fix a and add b.
+
+b: 0.2.0-rc.1 -> 0.2.0 (packages/b/CHANGELOG.md)
+
+## 0.2.0 - 2020-01-01
+
+### Minor Changes
+
+- Fix a and add b.",
+    );
+
+    parse_changelog([&args[..], &["--write", "--delete"]].concat())
+        .current_dir(&dir)
+        .assert_success();
+    assert_eq!(
+        fs::read_to_string(dir.join("packages/b/CHANGELOG.md")).unwrap(),
+        "# b\n\n## 0.2.0 - 2020-01-01\n\n### Minor Changes\n\n- Fix a and add b.\n\n## 0.2.0-rc.1\n\n- Bar.\n"
+    );
+    assert!(!dir.join(".changeset/brave-cats-run.md").exists());
+    assert!(dir.join(".changeset/README.md").exists());
+
+    fs::write(dir.join(".changeset/new.md"), "---\nc: major\n---\n\nFoo.\n").unwrap();
+    parse_changelog(args)
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("no changelog found for package 'c'");
+    fs::write(dir.join(".changeset/new.md"), "---\na: huge\n---\n\nFoo.\n").unwrap();
+    parse_changelog(args)
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("unknown bump type 'huge'");
+
+    // Nothing is written if one of the changelogs cannot be updated.
+    fs::write(dir.join(".changeset/new.md"), "---\na: major\nb: patch\n---\n\nFoo.\n").unwrap();
+    fs::write(dir.join("packages/b/CHANGELOG.md"), "# b\n").unwrap();
+    let a = fs::read_to_string(dir.join("packages/a/CHANGELOG.md")).unwrap();
+    parse_changelog(["changeset", "--write"])
+        .current_dir(&dir)
+        .assert_failure()
+        .stderr_contains("no release was found in");
+    assert_eq!(fs::read_to_string(dir.join("packages/a/CHANGELOG.md")).unwrap(), a);
+}
+
 type ChangelogOwned = IndexMap<String, ReleaseOwned>;
 
 #[derive(Debug, PartialEq, Deserialize)]
@@ -155,6 +1606,15 @@ struct ReleaseOwned {
     notes: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct WorkspaceRelease {
+    name: String,
+    version: String,
+    path: String,
+    title: String,
+    notes: String,
+}
+
 #[test]
 fn json() {
     let text = parse_changelog(["tests/fixtures/pin-project.md", "--json"]).assert_success().stdout;
@@ -181,6 +1641,20 @@ fn json() {
     .stdout;
     let changelog: ChangelogOwned = serde_json::from_str(&text).unwrap();
     assert_eq!(changelog.len(), 54);
+
+    let changelog = "## 0.1.0\n\n- Fix crash (#1, [a1b2c3d](../../commit/a1b2c3d), @user)\n";
+    let text = parse_changelog(["-", "--json"]).spawn_with_stdin(changelog).assert_success().stdout;
+    let changelog: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(
+        changelog["0.1.0"]["references"],
+        serde_json::json!({ "issues": [1], "commits": ["a1b2c3d"], "mentions": ["user"] })
+    );
+
+    let changelog = "# Changelog\n\n## [0.2.0] - 2024-01-01\n\n### 0.1.0\n\n## 0.1.0\n";
+    let text = parse_changelog(["-", "--json"]).spawn_with_stdin(changelog).assert_success().stdout;
+    let changelog: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(changelog["0.2.0"]["anchor"], "020---2024-01-01");
+    assert_eq!(changelog["0.1.0"]["anchor"], "010-1");
 }
 
 #[test]
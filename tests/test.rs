@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use std::{error::Error as _, path::Path};
+use std::{borrow::Cow, error::Error as _, path::Path};
 
 use fs_err as fs;
 use parse_changelog::*;
@@ -355,6 +355,16 @@ a
     assert_eq!(changelog["0.0.1"].notes, "");
     assert_eq!(changelog["0.0.0"].title, "0.0.0 <!--");
     assert_eq!(changelog["0.0.0"].notes, "a\n-->\n<!--\n# 0.0.0 -->");
+
+    // `--->` closes a comment.
+    let changelog = parse("# 0.2.0\n<!-- a --->\n# 0.1.0\n").unwrap();
+    assert_eq!(changelog.len(), 2);
+    assert_eq!(changelog["0.2.0"].notes, "<!-- a --->");
+
+    // Comment delimiters in code spans are not special for the parser.
+    let changelog = parse("# 0.2.0\n<!-- `-->`\n# 0.1.0\n").unwrap();
+    assert_eq!(changelog.len(), 2);
+    assert_eq!(changelog["0.2.0"].notes, "<!-- `-->`");
 }
 
 #[test]
@@ -425,6 +435,797 @@ fn link() {
     assert_eq!(changelog["0.1.0"].title_no_link(), "0.1.0");
 }
 
+#[test]
+fn package() {
+    let text = "\
+## [Unreleased]
+
+## my-crate 0.2.0
+
+- Bug fixes.
+
+## [my-crate-macros 0.2.0]
+
+- Added `foo` macro.
+
+## my-crate 0.1.0
+
+Initial release
+";
+    let mut parser = Parser::new();
+    parser.prefix_format(r"^((?<package>[0-9A-Za-z_-]+) )?").unwrap();
+    let changelog = parser.parse_packages(text).unwrap();
+    assert_eq!(changelog.len(), 4);
+    assert_eq!(changelog[0].package, None);
+    assert_eq!(changelog[0].version, "Unreleased");
+    assert_eq!(changelog[&(Some("my-crate"), "0.2.0")].notes, "- Bug fixes.");
+    assert_eq!(changelog[&(Some("my-crate-macros"), "0.2.0")].package, Some("my-crate-macros"));
+    assert_eq!(changelog[&(Some("my-crate-macros"), "0.2.0")].notes, "- Added `foo` macro.");
+    assert_eq!(changelog[&(Some("my-crate"), "0.1.0")].title, "my-crate 0.1.0");
+    // Parser::parse is keyed only by version.
+    assert_eq!(parser.parse(text).unwrap_err().to_string(), "multiple release notes for '0.2.0'");
+    // Without a group named "package".
+    let changelog = Parser::new().prefix_format("my-crate ").unwrap().parse(text).unwrap();
+    assert_eq!(changelog.len(), 3);
+    assert_eq!(changelog["0.2.0"].notes, "- Bug fixes.");
+    assert_eq!(changelog["0.2.0"].package, None);
+
+    let text = "## a 0.1.0\n## b 0.1.0\n## a 0.1.0\n";
+    assert_eq!(
+        parser.parse_packages(text).unwrap_err().to_string(),
+        "multiple release notes for '0.1.0' of 'a'"
+    );
+    assert_eq!(
+        parser.parse_packages("# foo\n").unwrap_err().to_string(),
+        "no release note was found"
+    );
+}
+
+#[cfg(feature = "html")]
+#[test]
+fn html() {
+    let text = "\
+## [Unreleased]
+
+## [0.2.0] - 2020-02-01
+
+### Added
+
+- `foo` <bar> | ~~baz~~
+
+| a | b |
+| - | - |
+| 1 | 2 |
+
+## 0.2.0 - 2020-02-01
+
+Duplicate title.
+";
+    let releases: Vec<_> = parse_iter(text).collect();
+
+    assert_eq!(
+        render_html(&releases[1..2], &HtmlOptions::new()),
+        "<h2>0.2.0 - 2020-02-01</h2>\n<h3>Added</h3>\n\
+         <ul>\n<li><code>foo</code> <bar> | <del>baz</del></li>\n</ul>\n\
+         <table><thead><tr><th>a</th><th>b</th></tr></thead><tbody>\n\
+         <tr><td>1</td><td>2</td></tr>\n</tbody></table>\n"
+    );
+    assert_eq!(
+        render_html(&releases, HtmlOptions::new().anchors(true).heading_offset(4)),
+        "<h6 id=\"unreleased\">Unreleased</h6>\n\
+         <h6 id=\"020---2020-02-01\">0.2.0 - 2020-02-01</h6>\n<h6>Added</h6>\n\
+         <ul>\n<li><code>foo</code> <bar> | <del>baz</del></li>\n</ul>\n\
+         <table><thead><tr><th>a</th><th>b</th></tr></thead><tbody>\n\
+         <tr><td>1</td><td>2</td></tr>\n</tbody></table>\n\
+         <h6 id=\"020---2020-02-01-1\">0.2.0 - 2020-02-01</h6>\n<p>Duplicate title.</p>\n"
+    );
+    assert_eq!(
+        render_html(&releases[2..], HtmlOptions::new().standalone(true)),
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>0.2.0 - 2020-02-01</title>\n</head>\n<body>\n\
+         <h2>0.2.0 - 2020-02-01</h2>\n<p>Duplicate title.</p>\n</body>\n</html>\n"
+    );
+
+    // Release headings keep their levels in the changelog.
+    let releases: Vec<_> = parse_iter("# 1.0.0\n\n## Added\n\n- foo\n").collect();
+    assert_eq!(
+        render_html(&releases, HtmlOptions::new().heading_offset(1)),
+        "<h2>1.0.0</h2>\n<h3>Added</h3>\n<ul>\n<li>foo</li>\n</ul>\n"
+    );
+}
+
+#[test]
+fn notes_plain() {
+    let text = "\
+## 0.2.0
+
+Some **important** _changes_, see [the docs][docs] and <https://example.com>.
+
+### Added
+
+- Add `foo_bar` and ~~baz~~ [#1](https://github.com/owner/repo/pull/1).
+  This is a long line that
+  continues here.
+* Fix [\\[bracket\\]] handling.
+<!-- comment -->
+1. `a * b`
+
+> Quote.
+
+```rust
+let _x = **y;
+```
+
+[docs]: https://docs.rs/foo
+";
+    let changelog = parse(text).unwrap();
+    let release = &changelog["0.2.0"];
+
+    assert_eq!(
+        release.notes_plain(),
+        "Some important changes, see the docs (https://docs.rs/foo) and https://example.com.\n\n\
+         Added\n\n\
+         - Add foo_bar and baz #1 (https://github.com/owner/repo/pull/1).\n  \
+         This is a long line that\n  continues here.\n\
+         - Fix [bracket] handling.\n\
+         1. a * b\n\n\
+         Quote.\n\n\
+         let _x = **y;"
+    );
+    assert_eq!(
+        release.notes_plain_with(PlainTextOptions::new().link_urls(false).width(30)),
+        "Some important changes, see\nthe docs and\nhttps://example.com.\n\n\
+         Added\n\n\
+         - Add foo_bar and baz #1. This\n  \
+         is a long line that\n  continues here.\n\
+         - Fix [bracket] handling.\n\
+         1. a * b\n\n\
+         Quote.\n\n\
+         let _x = **y;"
+    );
+
+    // Empty list items keep their markers, and Setext-style headings may span
+    // multiple lines.
+    let changelog = parse("# 0.1.0\n\nSome\nheading\n---\n- a\n-\n- b\n").unwrap();
+    let release = &changelog["0.1.0"];
+    assert_eq!(release.notes_plain(), "Some\nheading\n- a\n-\n- b");
+    assert_eq!(
+        release.notes_plain_with(PlainTextOptions::new().width(30)),
+        "Some heading\n- a\n-\n- b"
+    );
+
+    // Only delimiters that open and close emphasis are removed, and URLs are
+    // left as is.
+    let changelog = parse(
+        "# 0.1.0\n\n\
+         - snake_case_name\n- a_b_ and _a_b_\n- 2*3 = 6\n- foo*bar*baz\n- **a*\n\
+         - https://example.com/_a_/*b*\n- *https://example.com/a_*\n",
+    )
+    .unwrap();
+    assert_eq!(
+        changelog["0.1.0"].notes_plain(),
+        "- snake_case_name\n- a_b_ and a_b\n- 2*3 = 6\n- foobarbaz\n- **a*\n\
+         - https://example.com/_a_/*b*\n- https://example.com/a_"
+    );
+}
+
+#[test]
+fn link_definitions() {
+    let text = "\
+[a]: https://example.com/a \"A\"
+   [B]: <b c.md>
+    [indented]: d
+
+```
+[code]: e
+```
+
+<!--
+[comment]: f
+-->
+[x][y]: g
+[empty]:
+";
+    let definitions = parse_changelog::link_definitions(text);
+    let definitions: Vec<_> = definitions.iter().map(|d| (d.label, d.destination)).collect();
+    assert_eq!(definitions, [("a", "https://example.com/a"), ("B", "b c.md")]);
+}
+
+#[test]
+fn resolve_links() {
+    let text = "\
+- [docs](docs/guide.md \"Guide\"), [#12](../../pull/12), [root](/owner/repo/issues)
+- [q](./a/../b.md?x=1#y), ![img](<img/a b.png>), [x][ref], [y]
+- [abs](https://example.com), [mailto](mailto:a@example.com), [frag](#foo), [no link
+  [ref]: ../../compare/v0.1.0...v1.0.0
+
+```
+[a](b)
+```
+";
+    let mut resolver = LinkResolver::new("https://github.com/owner/repo/blob/{rev}/CHANGELOG.md");
+    resolver.revision("v1.0.0");
+    assert_eq!(
+        resolver.resolve(text),
+        "\
+- [docs](https://github.com/owner/repo/blob/v1.0.0/docs/guide.md \"Guide\"), \
+[#12](https://github.com/owner/repo/pull/12), [root](https://github.com/owner/repo/issues)
+- [q](https://github.com/owner/repo/blob/v1.0.0/b.md?x=1#y), \
+![img](<https://github.com/owner/repo/blob/v1.0.0/img/a b.png>), [x][ref], [y]
+- [abs](https://example.com), [mailto](mailto:a@example.com), [frag](#foo), [no link
+  [ref]: https://github.com/owner/repo/compare/v0.1.0...v1.0.0
+
+```
+[a](b)
+```
+"
+    );
+    assert_eq!(
+        LinkResolver::new("https://example.com").resolve("[a](b/../c/)"),
+        "[a](https://example.com/c/)"
+    );
+    assert_eq!(
+        LinkResolver::new("https://example.com/x/").resolve("[a](b/..)"),
+        "[a](https://example.com/x/)"
+    );
+    assert_eq!(LinkResolver::new("docs/").resolve("[a](b)"), "[a](b)");
+    // Links in code blocks and code spans are left as is.
+    assert_eq!(
+        LinkResolver::new("https://example.com/")
+            .resolve("````\n```\n[a](b)\n````\n\n- `[x](y)`, ``[`](`)``, [z](w)\n"),
+        "````\n```\n[a](b)\n````\n\n- `[x](y)`, ``[`](`)``, [z](https://example.com/w)\n"
+    );
+}
+
+#[test]
+fn auto_link() {
+    let text = "\
+- Fix crash (#123). Closes GH-45, other/repo.rs#6, thanks @user-1!
+- Keep `#1`, [#2](https://example.com/2), <https://example.com/#3>, https://example.com/#4, \
+a#5, &#6;, #7a, a@b.com, @types/node, \\#8 <!-- #9 -->
+<!--
+#10
+-->
+- #11
+
+```
+#12
+```
+
+[#13]: https://example.com/13
+";
+    let linker = AutoLinker::new("https://github.com/owner/repo/");
+    assert_eq!(
+        linker.link(text),
+        "\
+- Fix crash ([#123](https://github.com/owner/repo/issues/123)). \
+Closes [GH-45](https://github.com/owner/repo/issues/45), \
+[other/repo.rs#6](https://github.com/other/repo.rs/issues/6), \
+thanks [@user-1](https://github.com/user-1)!
+- Keep `#1`, [#2](https://example.com/2), <https://example.com/#3>, https://example.com/#4, \
+a#5, &#6;, #7a, a@b.com, @types/node, \\#8 <!-- #9 -->
+<!--
+#10
+-->
+- [#11](https://github.com/owner/repo/issues/11)
+
+```
+#12
+```
+
+[#13]: https://example.com/13
+"
+    );
+    assert_eq!(
+        AutoLinker::new("https://gitlab.com/group/project")
+            .issue_url("{repository}/-/issues/{number}")
+            .link("Fix #1"),
+        "Fix [#1](https://gitlab.com/group/project/-/issues/1)"
+    );
+    // References to other repositories and users are linked to the same host,
+    // even if the repository is in a subgroup.
+    assert_eq!(
+        AutoLinker::new("https://gitlab.com/group/subgroup/project").link("other/repo#1 @user"),
+        "[other/repo#1](https://gitlab.com/other/repo/issues/1) [@user](https://gitlab.com/user)"
+    );
+    // Comment delimiters in code spans don't open comments.
+    assert_eq!(
+        linker.link("`<!--` #1 <!-- #2 --> #3"),
+        "`<!--` [#1](https://github.com/owner/repo/issues/1) <!-- #2 --> \
+         [#3](https://github.com/owner/repo/issues/3)"
+    );
+    assert!(matches!(linker.link("No references (1#)."), std::borrow::Cow::Borrowed(_)));
+}
+
+#[test]
+fn references() {
+    let text = "\
+## 0.1.0
+
+- Fix crash (#12, GH-13, other/repo#14). Thanks @user1 and @user2!
+- Fix [#15](https://github.com/owner/repo/pull/15) ([`a1b2c3d`], `e4f5a6b7`, 0123abcd).
+- See <https://github.com/owner/repo/issues/16#issuecomment-1> and \
+https://gitlab.com/group/project/-/merge_requests/17.
+- Keep `#18`, a#19, @types/node, 1234567, deadbeef, A1B2C3D, @user1 <!-- #20 -->
+
+```
+#21 @user3 a1b2c3d4
+```
+
+[`a1b2c3d`]: https://github.com/owner/repo/commit/a1b2c3d4e5f6a7b8c9d0a1b2c3d4e5f6a7b8c9d0
+[#12]: https://github.com/owner/repo/issues/22
+
+- Both forms of references to other repositories are included: other/repo#23, \
+https://github.com/other/repo/issues/24.
+";
+    let changelog = parse(text).unwrap();
+    let references = changelog["0.1.0"].references();
+    assert_eq!(references.issues, [12, 13, 14, 15, 16, 17, 22, 23, 24]);
+    assert_eq!(
+        references.commits,
+        ["a1b2c3d", "e4f5a6b7", "0123abcd", "a1b2c3d4e5f6a7b8c9d0a1b2c3d4e5f6a7b8c9d0"]
+    );
+    assert_eq!(references.mentions, ["user1", "user2"]);
+
+    let changelog = parse("## 0.1.0\n").unwrap();
+    assert_eq!(changelog["0.1.0"].references(), References::default());
+}
+
+#[test]
+fn heading_offset() {
+    let text = "\
+# 0.1.0
+
+Added
+-----
+
+- Foo
+
+### Fixed ###
+
+```
+## Bar
+```
+
+<!--
+## Baz
+-->
+
+---
+
+###### Changed
+";
+    let changelog = parse(text).unwrap();
+    let release = &changelog["0.1.0"];
+    assert_eq!(
+        release.notes_with_heading_offset(1),
+        "### Added\n\n- Foo\n\n#### Fixed ###\n\n```\n## Bar\n```\n\n<!--\n## Baz\n-->\n\n---\n\n###### Changed"
+    );
+    assert_eq!(
+        release.notes_with_heading_offset(-2),
+        "# Added\n\n- Foo\n\n# Fixed ###\n\n```\n## Bar\n```\n\n<!--\n## Baz\n-->\n\n---\n\n#### Changed"
+    );
+    assert_eq!(release.notes_with_top_heading_level(2), release.notes);
+    assert_eq!(release.notes_with_heading_offset(0), release.notes);
+}
+
+#[test]
+fn strip_comments() {
+    let text = "\
+## 0.1.0
+
+<!-- Hidden instructions:
+don't forget to update docs. -->
+
+- Foo <!-- #1 --> bar <!-- #2 -->
+- Baz
+  <!-- TODO -->
+
+<!-- a --> <!-- b -->
+
+```
+<!-- code -->
+```
+
+~~~~
+```
+<!-- code -->
+~~~~
+
+- Qux
+<!--
+-->
+";
+    let changelog = parse(text).unwrap();
+    assert_eq!(
+        changelog["0.1.0"].notes_without_comments(),
+        "- Foo bar\n- Baz\n\n```\n<!-- code -->\n```\n\n~~~~\n```\n<!-- code -->\n~~~~\n\n- Qux"
+    );
+
+    let changelog = parse("## 0.1.0\n\n- Foo\n").unwrap();
+    assert!(matches!(changelog["0.1.0"].notes_without_comments(), Cow::Borrowed("- Foo")));
+
+    // Comment delimiters in code spans are not comments.
+    let changelog =
+        parse("## 0.1.0\n\n- a `<!-- x -->` b\n- `<!--` c <!-- d -->\n\n## 0.0.1\n").unwrap();
+    assert_eq!(changelog["0.1.0"].notes_without_comments(), "- a `<!-- x -->` b\n- `<!--` c");
+    assert_eq!(changelog.len(), 2);
+}
+
+#[test]
+fn truncate() {
+    let text = "\
+## 0.1.0
+
+Some paragraph
+spanning two lines.
+
+- Foo
+  - Bar
+- Baz
+
+```rust
+let a = 1;
+
+let b = 2;
+```
+
+- Qux
+";
+    let changelog = parse(text).unwrap();
+    let release = &changelog["0.1.0"];
+    let truncated = |options: &TruncateOptions| release.notes_truncated(options).into_owned();
+
+    assert!(matches!(release.notes_truncated(&TruncateOptions::new()), Cow::Borrowed(_)));
+    assert!(matches!(
+        release.notes_truncated(TruncateOptions::new().max_lines(14)),
+        Cow::Borrowed(_)
+    ));
+    assert_eq!(truncated(TruncateOptions::new().max_lines(1)), "");
+    assert_eq!(
+        truncated(TruncateOptions::new().max_lines(3)),
+        "Some paragraph\nspanning two lines."
+    );
+    assert_eq!(
+        truncated(TruncateOptions::new().max_lines(5)),
+        "Some paragraph\nspanning two lines.\n\n- Foo\n  - Bar"
+    );
+    // Never cut inside a code block.
+    assert_eq!(
+        truncated(TruncateOptions::new().max_lines(11)),
+        "Some paragraph\nspanning two lines.\n\n- Foo\n  - Bar\n- Baz"
+    );
+    assert_eq!(
+        truncated(TruncateOptions::new().max_bytes(40)),
+        "Some paragraph\nspanning two lines."
+    );
+
+    let mut options = TruncateOptions::new();
+    options.max_lines(6).changelog_url("https://example.com/CHANGELOG.md#top");
+    assert_eq!(
+        truncated(&options),
+        "Some paragraph\nspanning two lines.\n\n- Foo\n\n\
+         [See the full changelog](https://example.com/CHANGELOG.md#010)"
+    );
+    options.link_text("More");
+    assert_eq!(truncated(options.max_lines(2)), "[More](https://example.com/CHANGELOG.md#010)");
+    // The link is omitted if it doesn't fit.
+    assert_eq!(truncated(options.max_lines(1).max_bytes(10)), "");
+
+    // Never cut inside a comment.
+    let changelog = parse("## 0.1.0\n\n- Foo\n\n<!--\n\n- Bar\n\n-->\n\n- Baz\n- Qux\n").unwrap();
+    let mut options = TruncateOptions::new();
+    options.max_lines(7).changelog_url("https://example.com/CHANGELOG.md");
+    assert_eq!(
+        changelog["0.1.0"].notes_truncated(&options),
+        "- Foo\n\n[See the full changelog](https://example.com/CHANGELOG.md#010)"
+    );
+    assert_eq!(
+        changelog["0.1.0"].notes_truncated(options.max_lines(9)),
+        "- Foo\n\n<!--\n\n- Bar\n\n-->\n\n[See the full changelog](https://example.com/CHANGELOG.md#010)"
+    );
+}
+
+#[test]
+fn heading_range() {
+    let text = "\
+# Changelog
+
+  ## [0.2.0] - 2024-01-01
+
+```
+## 0.1.5
+```
+
+0.1.0 <!-- comment -->
+-------
+
+- Foo";
+    let changelog = parse_iter(text).collect::<Vec<_>>();
+    let headings = changelog
+        .iter()
+        .map(|release| (&text[release.heading_range()], release.heading_level()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        headings,
+        [("  ## [0.2.0] - 2024-01-01", 2), ("0.1.0 <!-- comment -->\n-------", 2)]
+    );
+
+    let text = "0.1.0\n===";
+    let changelog = parse(text).unwrap();
+    assert_eq!(changelog["0.1.0"].heading_range(), 0..text.len());
+    assert_eq!(changelog["0.1.0"].heading_level(), 1);
+}
+
+#[test]
+fn anchor() {
+    let text = "\
+# Changelog
+
+## [Unreleased]
+
+## [0.2.0] - 2024-01-01
+
+### Fixed
+
+- Foo
+
+```
+## 0.1.0
+```
+
+<!--
+## 0.1.0
+-->
+
+## Fixed
+
+## 0.1.0
+
+Fixed
+-----
+
+### 0.1.0-1
+
+## 0.1.0-1
+
+---
+
+## [0.0.1] (`v0.0.1`)
+
+[0.2.0]: https://github.com/owner/repo/compare/v0.1.0...v0.2.0
+";
+    let changelog = parse_iter(text).collect::<Vec<_>>();
+    let anchors = changelog.iter().map(Release::anchor).collect::<Vec<_>>();
+    assert_eq!(anchors, ["unreleased", "020---2024-01-01", "010", "010-1-1", "001-v001",]);
+
+    let changelog = parse(text).unwrap();
+    assert_eq!(changelog["0.2.0"].anchor(), "020---2024-01-01");
+
+    // Anchors depend on the position in the changelog, but are not part of
+    // the equality of releases.
+    let old = parse("## Unreleased\n\n## 1.0.0\n\n- Foo\n").unwrap();
+    let new = parse("## Unreleased\n\n### 1.0.0\n\n## 1.0.0\n\n- Foo\n").unwrap();
+    assert_eq!(old["1.0.0"], new["1.0.0"]);
+    assert_eq!(old["1.0.0"].anchor(), "100");
+    assert_eq!(new["1.0.0"].anchor(), "100-1");
+    #[cfg(feature = "html")]
+    assert_eq!(
+        render_html(new.values(), HtmlOptions::new().anchors(true)),
+        "<h2 id=\"unreleased\">Unreleased</h2>\n<h3>1.0.0</h3>\n\
+         <h2 id=\"100-1\">1.0.0</h2>\n<ul>\n<li>Foo</li>\n</ul>\n"
+    );
+}
+
+#[test]
+fn format() {
+    let text = "\
+# Changelog
+Intro.
+## [Unreleased]
+### Fixed
+* Foo
+  + Nested
+### Other
+* Other
+### Added
+* Bar
+
+```
+* Not a bullet
+## Not a heading
+```
+
+<!--
+* Comment
+### Removed
+-->
+## [0.1.0] - 2020-01-01 ##
+
+
+### Security:
++ Baz
+### Changed
+- Qux
+
+[other]: https://example.com
+[0.1.0]: https://example.com/0.1.0
+[Unreleased]: https://example.com/HEAD
+";
+    let formatted = "\
+# Changelog
+
+Intro.
+
+## [Unreleased]
+
+### Added
+
+- Bar
+
+```
+* Not a bullet
+## Not a heading
+```
+
+<!--
+* Comment
+### Removed
+-->
+
+### Other
+
+- Other
+
+### Fixed
+
+- Foo
+  - Nested
+
+## [0.1.0] - 2020-01-01
+
+### Changed
+
+- Qux
+
+### Security:
+
+- Baz
+
+[Unreleased]: https://example.com/HEAD
+[0.1.0]: https://example.com/0.1.0
+[other]: https://example.com
+";
+    let parser = Parser::new();
+    assert_eq!(parser.format(text, &FormatOptions::new()), formatted);
+    // Formatting is idempotent.
+    assert_eq!(parser.format(formatted, &FormatOptions::new()), formatted);
+    // The formatted changelog has the same releases.
+    let releases = parser.parse(text).unwrap();
+    let formatted_releases = parser.parse(formatted).unwrap();
+    assert_eq!(releases.keys().collect::<Vec<_>>(), formatted_releases.keys().collect::<Vec<_>>());
+
+    // Heading style and bullet.
+    let mut options = FormatOptions::new();
+    options.heading_style(HeadingStyle::Setext).bullet('*').unwrap();
+    let setext = parser.format(text, &options);
+    assert!(setext.starts_with("Changelog\n=========\n\nIntro.\n\n[Unreleased]\n------------\n"));
+    assert!(setext.contains("\n* Bar\n"));
+    assert!(setext.contains("\n* Not a bullet\n## Not a heading\n"));
+    assert_eq!(parser.format(&setext, &options), setext);
+    // By default, the style of the first level 1 or 2 heading is used.
+    assert!(parser.format(&setext, &FormatOptions::new()).starts_with("Changelog\n=========\n"));
+    options.heading_style(HeadingStyle::Atx).bullet('-').unwrap();
+    assert_eq!(parser.format(&setext, &options), formatted);
+    assert_eq!(
+        FormatOptions::new().bullet('.').unwrap_err().to_string(),
+        "invalid bullet '.' (expected '-', '*', or '+')"
+    );
+
+    // Line endings are preserved.
+    let crlf = text.replace('\n', "\r\n");
+    assert_eq!(parser.format(&crlf, &FormatOptions::new()), formatted.replace('\n', "\r\n"));
+
+    // Formatting is idempotent even if the changelog ends in an unclosed code
+    // block or comment.
+    for (text, formatted) in [
+        ("\n<!--x\n# a\n", "\n<!--x\n# a\n"),
+        ("# a\n\n```\n# b\n\n\n", "# a\n\n```\n# b\n"),
+        ("# a\n\n<!--\n  \n", "# a\n\n<!--\n"),
+    ] {
+        assert_eq!(parser.format(text, &FormatOptions::new()), formatted);
+        assert_eq!(parser.format(formatted, &FormatOptions::new()), formatted);
+    }
+}
+
+#[test]
+fn convert_headings() {
+    let setext = "\
+Changelog
+=========
+Intro.
+
+Unreleased
+----------
+
+### Fixed
+
+- Foo
+
+```
+Not a heading
+-------------
+```
+
+<!--
+Not a heading
+-------------
+-->
+
+0.1.0 - 2020-01-01
+------------------
+
+Other
+-----
+";
+    let atx = "\
+# Changelog
+Intro.
+
+## Unreleased
+
+### Fixed
+
+- Foo
+
+```
+Not a heading
+-------------
+```
+
+<!--
+Not a heading
+-------------
+-->
+
+## 0.1.0 - 2020-01-01
+
+## Other
+";
+    let parser = Parser::new();
+    assert_eq!(parser.convert_headings(setext, HeadingStyle::Atx, false), atx);
+    assert_eq!(parser.convert_headings(atx, HeadingStyle::Atx, false), atx);
+    assert_eq!(parser.convert_headings(atx, HeadingStyle::Setext, false), setext);
+    assert_eq!(parser.convert_headings(setext, HeadingStyle::Setext, false), setext);
+
+    // Only release headings.
+    assert_eq!(
+        parser.convert_headings(setext, HeadingStyle::Atx, true),
+        setext
+            .replace("Unreleased\n----------", "## Unreleased")
+            .replace("0.1.0 - 2020-01-01\n------------------", "## 0.1.0 - 2020-01-01")
+    );
+    assert_eq!(
+        parser.convert_headings(atx, HeadingStyle::Setext, true),
+        atx.replace("## Unreleased", "Unreleased\n----------")
+            .replace("## 0.1.0 - 2020-01-01", "0.1.0 - 2020-01-01\n------------------")
+    );
+    assert_eq!(parser.convert_headings("# Foo\n", HeadingStyle::Setext, true), "# Foo\n");
+
+    // Closing sequences and level 3 headings. A blank line is inserted
+    // between a paragraph and a Setext-style heading.
+    assert_eq!(
+        parser.convert_headings("# A #\nText\n## B\n### C\n", HeadingStyle::Setext, false),
+        "A\n===\nText\n\nB\n---\n### C\n"
+    );
+
+    // Line endings are preserved.
+    assert_eq!(
+        parser.convert_headings(&setext.replace('\n', "\r\n"), HeadingStyle::Atx, false),
+        atx.replace('\n', "\r\n")
+    );
+}
+
 #[test]
 #[cfg_attr(miri, ignore)] // Miri is too slow
 fn pin_project() {
@@ -59,6 +59,11 @@ fn gen_serde_impl() {
                         0,
                         "gen_serde_impl doesn't support const generics yet; consider excluding `{path_string}`"
                     );
+                    // Private fields are implementation details.
+                    let fields: Vec<_> = fields
+                        .iter()
+                        .filter(|f| matches!(f.vis, syn::Visibility::Public(..)))
+                        .collect();
                     let num_fields = Literal::usize_unsuffixed(fields.len());
                     let fields = fields.iter().map(|syn::Field { ident, .. }| {
                         let name = ident.as_ref().unwrap().to_string();